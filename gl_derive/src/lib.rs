@@ -0,0 +1,131 @@
+//! Derive macros for gl_obj, which re-exports them.
+//! `#[derive(Std430)]` and `#[derive(Std140)]` lay out a struct like GLSL does in a block, see `gl_obj::Std430`.
+//! `#[derive(Vertex)]` describes an interleaved vertex struct, see `gl_obj::Vertex`.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse_macro_input;
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Error;
+use syn::Field;
+use syn::Fields;
+
+#[proc_macro_derive(Std430)]
+pub fn derive_std430(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	std_layout(&input, quote!(::gl_obj::Std430), 1).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+#[proc_macro_derive(Std140)]
+pub fn derive_std140(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	std_layout(&input, quote!(::gl_obj::Std140), 16).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+#[proc_macro_derive(Vertex)]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	vertex(&input).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+// Implement gl_obj::Vertex: one attribute per field, with the field's name, type and offset.
+fn vertex(input: &DeriveInput) -> Result<TokenStream2, Error> {
+	let name = &input.ident;
+	if !input.generics.params.is_empty() {
+		return Err(Error::new_spanned(&input.generics, "vertex types cannot be generic"));
+	}
+	let fields = named_fields(input)?;
+	let idents: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+	let names: Vec<_> = idents.iter().map(|i| i.to_string()).collect();
+	let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+	Ok(quote! {
+		impl ::gl_obj::Vertex for #name {
+			fn attributes() -> Vec<::gl_obj::VertexAttrib> {
+				vec![#(::gl_obj::VertexAttrib::new::<#types>(#names, ::std::mem::offset_of!(#name, #idents))),*]
+			}
+		}
+	})
+}
+
+// The fields of a struct with at least one named field.
+fn named_fields(input: &DeriveInput) -> Result<&Punctuated<Field, Comma>, Error> {
+	match &input.data {
+		Data::Struct(s) => match &s.fields {
+			Fields::Named(f) if !f.named.is_empty() => Ok(&f.named),
+			_ => Err(Error::new_spanned(&input.ident, "expected a struct with named fields")),
+		},
+		_ => Err(Error::new_spanned(&input.ident, "expected a struct with named fields")),
+	}
+}
+
+// Implement layout trait `tr` (Std430 or Std140) for a struct with named fields.
+// Structs are aligned to at least `min_align` bytes.
+fn std_layout(input: &DeriveInput, tr: TokenStream2, min_align: usize) -> Result<TokenStream2, Error> {
+	let name = &input.ident;
+	if !input.generics.params.is_empty() {
+		return Err(Error::new_spanned(&input.generics, "GLSL structs cannot be generic"));
+	}
+	let fields = named_fields(input)?;
+
+	let idents: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+	let names: Vec<_> = idents.iter().map(|i| i.to_string()).collect();
+	let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+	let indices: Vec<_> = (0..fields.len()).collect();
+	let n = fields.len();
+	let glsl_name = name.to_string();
+
+	// (alignment, size) of each field, and their offsets, as consts local to each method.
+	let layout = quote! {
+		const FIELDS: [(usize, usize); #n] = [#((<#types as #tr>::ALIGN, <#types as #tr>::SIZE)),*];
+		const OFFSETS: [usize; #n] = [#(::gl_obj::std_field_offset(&FIELDS, #indices)),*];
+	};
+
+	Ok(quote! {
+		impl #tr for #name {
+			const ALIGN: usize = ::gl_obj::std_struct_align(&[#((<#types as #tr>::ALIGN, <#types as #tr>::SIZE)),*], #min_align);
+			const SIZE: usize = ::gl_obj::std_struct_size(&[#((<#types as #tr>::ALIGN, <#types as #tr>::SIZE)),*], <#name as #tr>::ALIGN);
+
+			fn glsl_type() -> String {
+				#glsl_name.into()
+			}
+
+			fn declare(decls: &mut Vec<String>) {
+				#(<#types as #tr>::declare(decls);)*
+				let members = [#(::gl_obj::std_member_decl(&<#types as #tr>::glsl_type(), #names)),*];
+				let decl = format!("struct {} {{\n{}}};\n", #glsl_name, members.concat());
+				if !decls.contains(&decl) {
+					decls.push(decl);
+				}
+			}
+
+			fn locate(path: &str) -> Option<(usize, u32)> {
+				#layout
+				let (field, rest) = ::gl_obj::std_split_field(path);
+				match field {
+					#(#names => {
+						let (offset, typ) = <#types as #tr>::locate(rest)?;
+						Some((OFFSETS[#indices] + offset, typ))
+					})*
+					_ => None,
+				}
+			}
+
+			fn write(&self, out: &mut [u8]) {
+				#layout
+				#(<#types as #tr>::write(&self.#idents, &mut out[OFFSETS[#indices]..]);)*
+			}
+
+			fn read(bytes: &[u8]) -> Self {
+				#layout
+				Self {
+					#(#idents: <#types as #tr>::read(&bytes[OFFSETS[#indices]..]),)*
+				}
+			}
+		}
+	})
+}
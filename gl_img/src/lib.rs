@@ -1,9 +1,11 @@
 extern crate image;
+use gl::types::*;
 use gl_obj::*;
 
-pub fn load_image(fname: &str) -> Texture {
+#[track_caller]
+pub fn load_image(fname: &str) -> Texture<Srgb8Alpha8> {
 	let (size, data) = load_image_data(fname);
-	Texture::new2d(gl::SRGB8_ALPHA8, size).sub_image2d(0, 0, 0, size.0, size.1, gl::RGBA, gl::UNSIGNED_BYTE, &data)
+	Texture::from_data2d(size, &data)
 }
 
 pub fn load_image_data(fname: &str) -> (uvec2, Vec<[u8; 4]>) {
@@ -15,3 +17,19 @@ pub fn load_image_data(fname: &str) -> (uvec2, Vec<[u8; 4]>) {
 	}
 	(size, data)
 }
+
+/// Save RGBA pixels, ordered bottom to top as returned by `Framebuffer::read_pixels`, to an image file.
+/// The format is deduced from the extension.
+pub fn save_image(fname: &str, size: uvec2, pixels: &[[u8; 4]]) {
+	let img = image::RgbaImage::from_fn(size.0, size.1, |x, y| {
+		let row = size.1 - 1 - y; // flip: GL's first row is at the bottom.
+		image::Rgba(pixels[(row * size.0 + x) as usize])
+	});
+	img.save(fname).expect("save image")
+}
+
+/// Save the color contents of a framebuffer (e.g. `Framebuffer::window`) to an image file.
+pub fn save_framebuffer(fname: &str, fb: &Framebuffer, attachment: GLenum) {
+	let pixels = fb.read_pixels(attachment, gl::RGBA, gl::UNSIGNED_BYTE);
+	save_image(fname, fb.size(), &pixels)
+}
@@ -0,0 +1,116 @@
+use super::*;
+use gl_safe::*;
+use std::marker::PhantomData;
+
+/// An array of `T` in a buffer object, laid out by the std430 rules, for use as a shader storage block.
+/// See `Std430` and `#[derive(Std430)]`.
+pub struct StorageBuffer<T: Std430> {
+	buffer: Buffer<u8>,
+	len: u32,
+	_type: PhantomData<T>,
+}
+
+impl<T> StorageBuffer<T>
+where
+	T: Std430,
+{
+	/// A buffer holding `data`, with storage flags `flags` (e.g. DYNAMIC_STORAGE_BIT to allow `sub_data`).
+	#[track_caller]
+	pub fn new(data: &[T], flags: GLbitfield) -> Self {
+		Self {
+			buffer: Buffer::new(&std430_bytes(data), flags),
+			len: data.len() as u32,
+			_type: PhantomData,
+		}
+	}
+
+	/// Number of elements.
+	pub fn len(&self) -> u32 {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// The underlying bytes.
+	pub fn buffer(&self) -> &Buffer<u8> {
+		&self.buffer
+	}
+
+	/// Overwrite elements starting at `offset` with `data`.
+	/// The buffer must have been created with DYNAMIC_STORAGE_BIT.
+	pub fn sub_data(&self, offset: u32, data: &[T]) {
+		if offset as usize + data.len() > self.len as usize {
+			panic!("StorageBuffer::sub_data: range {}..{} out of bounds (len {})", offset, offset as usize + data.len(), self.len)
+		}
+		self.buffer.sub_data(offset * T::STRIDE as u32, &std430_bytes(data))
+	}
+
+	/// Returns a copy of the buffer's contents.
+	pub fn get_data(&self) -> Vec<T> {
+		let bytes = self.buffer.get_data();
+		(0..self.len as usize).map(|i| T::read(&bytes[i * T::STRIDE..])).collect()
+	}
+
+	/// Bind the buffer to the binding point of storage block `block` in `program`,
+	/// after checking that the block is laid out like `T`, and that the buffer is large enough.
+	pub fn bind(&self, program: &Program, block: &str) -> Result<(), String> {
+		let info = cached_info(program.handle());
+		let b = info.check_storage_block::<T>(block)?;
+		if self.buffer.bytes() < b.data_size as usize {
+			return Err(format!("storage block `{}` needs {} bytes, buffer has {}", block, b.data_size, self.buffer.bytes()));
+		}
+		self.buffer.bind_base(gl::SHADER_STORAGE_BUFFER, b.binding);
+		Ok(())
+	}
+}
+
+/// A value of type `T` in a buffer object, laid out by the std140 rules, for use as a uniform block.
+/// Unlike individual uniforms, a uniform buffer can be shared by several programs, and updated in one call.
+/// See `Std140` and `#[derive(Std140)]`.
+pub struct UniformBuffer<T: Std140> {
+	buffer: Buffer<u8>,
+	_type: PhantomData<T>,
+}
+
+impl<T> UniformBuffer<T>
+where
+	T: Std140,
+{
+	/// A buffer holding `value`, which can be updated with `set`.
+	#[track_caller]
+	pub fn new(value: T) -> Self {
+		Self {
+			buffer: Buffer::new(&std140_bytes(&[value]), gl::DYNAMIC_STORAGE_BIT),
+			_type: PhantomData,
+		}
+	}
+
+	/// Replace the value.
+	pub fn set(&self, value: T) {
+		self.buffer.sub_data(0, &std140_bytes(&[value]))
+	}
+
+	/// Returns a copy of the value.
+	pub fn get(&self) -> T {
+		T::read(&self.buffer.get_data())
+	}
+
+	/// The underlying bytes.
+	pub fn buffer(&self) -> &Buffer<u8> {
+		&self.buffer
+	}
+
+	/// Bind the buffer to the binding point of uniform block `block` in `program`,
+	/// after checking that the block is laid out like `T`.
+	pub fn bind(&self, program: &Program, block: &str) -> Result<(), String> {
+		let info = cached_info(program.handle());
+		let b = info.check_uniform_block::<T>(block)?;
+		if self.buffer.bytes() < b.data_size as usize {
+			return Err(format!("uniform block `{}` needs {} bytes, buffer has {}", block, b.data_size, self.buffer.bytes()));
+		}
+		self.buffer.bind_base(gl::UNIFORM_BUFFER, b.binding);
+		Ok(())
+	}
+}
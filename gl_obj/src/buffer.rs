@@ -1,10 +1,13 @@
 use super::*;
 use gl_safe::*;
 use std::marker::PhantomData;
+use std::mem;
 
+/// A buffer object holding elements of type `T`. The GL buffer is deleted when the `Buffer` is dropped.
 pub struct Buffer<T: Sized + Copy + 'static> {
 	handle: GLuint,
 	len: u32,
+	flags: GLbitfield,
 	_type: PhantomData<T>,
 }
 
@@ -12,18 +15,31 @@ impl<T> Buffer<T>
 where
 	T: Sized + Copy + 'static,
 {
+	#[track_caller]
 	pub fn new(data: &[T], flags: GLbitfield) -> Self {
 		let mut s = Self::create();
 		s.storage(data, flags);
 		s
 	}
 
+	/// Create a buffer with room for `len` elements, with undefined contents.
+	#[track_caller]
+	pub fn new_uninit(len: u32, flags: GLbitfield) -> Self {
+		let mut s = Self::create();
+		s.storage_uninit(len, flags);
+		s
+	}
+
 	/// Create a buffer object.
 	/// http://docs.gl/gl4/glCreateBuffers
+	#[track_caller]
 	pub fn create() -> Self {
+		let handle = glCreateBuffer();
+		registry::track("Buffer", handle);
 		Self {
-			handle: glCreateBuffer(),
+			handle,
 			len: 0,
+			flags: 0,
 			_type: PhantomData,
 		}
 	}
@@ -33,6 +49,21 @@ where
 	pub fn storage(&mut self, data: &[T], flags: GLbitfield) {
 		glNamedBufferStorage(self.handle, data, flags);
 		self.len = data.len() as u32;
+		self.flags = flags;
+	}
+
+	/// Creates a buffer object's immutable data store with room for `len` elements.
+	/// The contents are undefined.
+	/// http://docs.gl/gl4/glBufferStorage
+	pub fn storage_uninit(&mut self, len: u32, flags: GLbitfield) {
+		glNamedBufferStorageSize(self.handle, (len as usize) * mem::size_of::<T>(), flags);
+		self.len = len;
+		self.flags = flags;
+	}
+
+	/// Returns the size of the buffer in bytes.
+	pub fn bytes(&self) -> usize {
+		(self.len as usize) * mem::size_of::<T>()
 	}
 
 	/// Returns the number of elements in the buffer.
@@ -44,6 +75,87 @@ where
 		self.handle
 	}
 
+	/// The flags passed to `storage`.
+	pub fn flags(&self) -> GLbitfield {
+		self.flags
+	}
+
+	/// Bind the buffer to indexed binding point `index` of `target`, e.g. SHADER_STORAGE_BUFFER.
+	/// http://docs.gl/gl4/glBindBufferBase
+	pub fn bind_base(&self, target: GLenum, index: u32) {
+		glBindBufferBase(target, index, self.handle)
+	}
+
+	/// Overwrite elements starting at `offset` with `data`.
+	/// The buffer must have been created with DYNAMIC_STORAGE_BIT.
+	/// http://docs.gl/gl4/glBufferSubData
+	pub fn sub_data(&self, offset: u32, data: &[T]) {
+		self.check_range("sub_data", offset, data.len() as u32);
+		if self.flags & gl::DYNAMIC_STORAGE_BIT == 0 {
+			panic!("Buffer::sub_data: buffer {} was not created with DYNAMIC_STORAGE_BIT", self.handle)
+		}
+		glNamedBufferSubData(self.handle, Self::byte_offset(offset), data)
+	}
+
+	/// Copy `len` elements from `src`, starting at `src_offset`, to this buffer starting at `dst_offset`.
+	/// The copy is performed on the GPU.
+	/// http://docs.gl/gl4/glCopyBufferSubData
+	pub fn copy_from(&self, src: &Buffer<T>, src_offset: u32, dst_offset: u32, len: u32) {
+		src.check_range("copy_from (source)", src_offset, len);
+		self.check_range("copy_from (destination)", dst_offset, len);
+		glCopyNamedBufferSubData(src.handle, self.handle, Self::byte_offset(src_offset), Self::byte_offset(dst_offset), Self::byte_offset(len))
+	}
+
+	/// Set all elements to `value`.
+	/// `T` must be 1, 2, 4, 8, 12 or 16 bytes large.
+	/// http://docs.gl/gl4/glClearBufferData
+	pub fn clear(&self, value: T) {
+		// Clear with the raw bit pattern of `value`, using an unsigned integer format of the same size.
+		let (internalformat, format, typ) = match mem::size_of::<T>() {
+			1 => (gl::R8UI, gl::RED_INTEGER, gl::UNSIGNED_BYTE),
+			2 => (gl::R16UI, gl::RED_INTEGER, gl::UNSIGNED_SHORT),
+			4 => (gl::R32UI, gl::RED_INTEGER, gl::UNSIGNED_INT),
+			8 => (gl::RG32UI, gl::RG_INTEGER, gl::UNSIGNED_INT),
+			12 => (gl::RGB32UI, gl::RGB_INTEGER, gl::UNSIGNED_INT),
+			16 => (gl::RGBA32UI, gl::RGBA_INTEGER, gl::UNSIGNED_INT),
+			n => panic!("Buffer::clear: unsupported element size: {} bytes", n),
+		};
+		glClearNamedBufferData(self.handle, internalformat, format, typ, &value)
+	}
+
+	/// Map `len` elements starting at `offset` into CPU memory, for as long as the returned `MappedBuffer` lives.
+	/// The buffer must have been created with MAP_PERSISTENT_BIT and the MAP_READ_BIT/MAP_WRITE_BIT in `access`.
+	/// `access` typically is MAP_WRITE_BIT | MAP_PERSISTENT_BIT | MAP_COHERENT_BIT.
+	/// http://docs.gl/gl4/glMapBufferRange
+	pub fn map_range(self, offset: u32, len: u32, access: GLbitfield) -> MappedBuffer<T> {
+		self.check_range("map_range", offset, len);
+		let needed = access & (gl::MAP_READ_BIT | gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT);
+		if self.flags & needed != needed || self.flags & gl::MAP_PERSISTENT_BIT == 0 {
+			panic!(
+				"Buffer::map_range: buffer {} storage flags {:#x} do not allow persistent mapping with access {:#x}",
+				self.handle, self.flags, access
+			)
+		}
+		let ptr = glMapNamedBufferRange(self.handle, Self::byte_offset(offset), Self::byte_offset(len), access) as *mut T;
+		MappedBuffer {
+			buffer: self,
+			ptr,
+			len,
+			access,
+			fence: None,
+		}
+	}
+
+	fn check_range(&self, func: &str, offset: u32, len: u32) {
+		if (offset as u64) + (len as u64) > (self.len as u64) {
+			panic!("Buffer::{}: range {}..{} out of bounds (len {})", func, offset, offset + len, self.len)
+		}
+	}
+
+	fn byte_offset(elements: u32) -> usize {
+		(elements as usize) * mem::size_of::<T>()
+	}
+
 	/// Copy the buffer's contents into `data`,
 	/// which must match in size.
 	pub fn copy_data(&self, data: &mut [T]) {
@@ -55,9 +167,19 @@ where
 
 	/// Returns a copy the buffer's contents.
 	pub fn get_data(&self) -> Vec<T> {
-		let mut data = Vec::<T>::with_capacity(self.len() as usize);
-		unsafe { data.set_len(self.len() as usize) };
-		glGetNamedBufferSubData(self.handle, 0 /*offset*/, &mut data);
+		self.get_sub_data(0, self.len())
+	}
+
+	/// Returns a copy of `len` elements, starting at element `start`.
+	pub fn get_sub_data(&self, start: u32, len: u32) -> Vec<T> {
+		if start + len > self.len() {
+			panic!("Buffer::get_sub_data: range {}..{} out of bounds: buffer len {}", start, start + len, self.len())
+		}
+		let mut data = Vec::<T>::with_capacity(len as usize);
+		if len != 0 {
+			glGetNamedBufferSubDataUninit(self.handle, Self::byte_offset(start), &mut data.spare_capacity_mut()[..len as usize]);
+			unsafe { data.set_len(len as usize) }; // initialized by GL
+		}
 		data
 	}
 }
@@ -67,16 +189,184 @@ where
 	T: Sized + Copy + 'static,
 {
 	fn drop(&mut self) {
-		println!("TODO: should we be dropping buffer {}?", self.handle);
-		//glDeleteBuffer(self.handle)
+		registry::untrack("Buffer", self.handle);
+		glDeleteBuffer(self.handle)
 	}
 }
 
-impl<T> Into<GLuint> for Buffer<T>
+/// A persistently mapped range of a buffer: memory shared between CPU and GPU,
+/// which stays valid while the GPU uses the buffer.
+///
+/// To avoid overwriting data the GPU is still reading (or reading data it is still writing),
+/// call `fence` after issuing the GL commands that use the buffer.
+/// CPU access through `as_slice`/`as_mut_slice` then first waits for those commands to complete.
+pub struct MappedBuffer<T: Sized + Copy + 'static> {
+	buffer: Buffer<T>,
+	ptr: *mut T,
+	len: u32,
+	access: GLbitfield,
+	fence: Option<Fence>,
+}
+
+impl<T> MappedBuffer<T>
 where
 	T: Sized + Copy + 'static,
 {
-	fn into(self) -> GLuint {
-		self.handle
+	/// Create a buffer of `len` elements, persistently and coherently mapped for reading and writing.
+	#[track_caller]
+	pub fn new(len: u32) -> Self {
+		let flags = gl::MAP_READ_BIT | gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+		Buffer::new_uninit(len, flags | gl::DYNAMIC_STORAGE_BIT).map_range(0, len, flags)
+	}
+
+	/// The underlying buffer, e.g. for binding to a shader.
+	pub fn buffer(&self) -> &Buffer<T> {
+		&self.buffer
+	}
+
+	/// Number of mapped elements.
+	pub fn len(&self) -> u32 {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Mark the end of GPU commands using the buffer.
+	/// The next CPU access will wait for them to finish.
+	pub fn fence(&mut self) {
+		self.fence = Some(Fence::new())
+	}
+
+	/// Block until the GPU commands before the last `fence` have completed.
+	pub fn wait(&mut self) {
+		if let Some(fence) = self.fence.take() {
+			fence.wait()
+		}
+	}
+
+	/// The mapped memory, after waiting for the GPU.
+	pub fn as_slice(&mut self) -> &[T] {
+		self.wait();
+		unsafe { std::slice::from_raw_parts(self.ptr, self.len as usize) }
+	}
+
+	/// The mapped memory, after waiting for the GPU.
+	/// Without MAP_COHERENT_BIT, writes must be made visible to the GPU with `flush`.
+	pub fn as_mut_slice(&mut self) -> &mut [T] {
+		self.wait();
+		unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len as usize) }
+	}
+
+	/// Make CPU writes visible to the GPU, for ranges mapped with MAP_FLUSH_EXPLICIT_BIT.
+	/// http://docs.gl/gl4/glFlushMappedBufferRange
+	pub fn flush(&self) {
+		if self.access & gl::MAP_FLUSH_EXPLICIT_BIT != 0 {
+			glFlushMappedNamedBufferRange(self.buffer.handle(), 0, (self.len as usize) * mem::size_of::<T>());
+		}
+	}
+}
+
+impl<T> Drop for MappedBuffer<T>
+where
+	T: Sized + Copy + 'static,
+{
+	fn drop(&mut self) {
+		glUnmapNamedBuffer(self.buffer.handle());
+	}
+}
+
+/// A growable array in GPU memory, like a `Vec<T>`.
+/// Appending (e.g. spawning particles) only uploads the new elements.
+/// When the capacity is exceeded, storage is re-allocated and the contents copied on the GPU.
+pub struct GpuVec<T: Sized + Copy + 'static> {
+	buffer: Buffer<T>,
+	len: u32,
+}
+
+impl<T> GpuVec<T>
+where
+	T: Sized + Copy + 'static,
+{
+	pub fn new() -> Self {
+		Self::with_capacity(16)
+	}
+
+	#[track_caller]
+	pub fn with_capacity(capacity: u32) -> Self {
+		Self {
+			buffer: Buffer::new_uninit(capacity.max(1), gl::DYNAMIC_STORAGE_BIT),
+			len: 0,
+		}
+	}
+
+	/// Number of elements.
+	pub fn len(&self) -> u32 {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Number of elements that fit without re-allocating.
+	pub fn capacity(&self) -> u32 {
+		self.buffer.len()
+	}
+
+	/// The underlying buffer. Only the first `len()` elements are valid.
+	/// The buffer changes when the capacity grows.
+	pub fn buffer(&self) -> &Buffer<T> {
+		&self.buffer
+	}
+
+	pub fn push(&mut self, value: T) {
+		self.extend_from_slice(&[value])
+	}
+
+	/// Append `data` at the end.
+	pub fn extend_from_slice(&mut self, data: &[T]) {
+		if data.is_empty() {
+			return;
+		}
+		self.reserve(data.len() as u32);
+		self.buffer.sub_data(self.len, data);
+		self.len += data.len() as u32;
+	}
+
+	/// Make sure there is room for at least `additional` more elements.
+	pub fn reserve(&mut self, additional: u32) {
+		let needed = self.len + additional;
+		if needed <= self.capacity() {
+			return;
+		}
+		let capacity = needed.max(2 * self.capacity());
+		let new = Buffer::new_uninit(capacity, gl::DYNAMIC_STORAGE_BIT);
+		new.copy_from(&self.buffer, 0, 0, self.len);
+		self.buffer = new;
+	}
+
+	/// Shorten to `len` elements, keeping the capacity.
+	pub fn truncate(&mut self, len: u32) {
+		self.len = self.len.min(len)
+	}
+
+	pub fn clear(&mut self) {
+		self.len = 0
+	}
+
+	/// Returns a copy of the contents.
+	pub fn get_data(&self) -> Vec<T> {
+		self.buffer.get_sub_data(0, self.len)
+	}
+}
+
+impl<T> Default for GpuVec<T>
+where
+	T: Sized + Copy + 'static,
+{
+	fn default() -> Self {
+		Self::new()
 	}
 }
@@ -0,0 +1,32 @@
+/*
+	Stream compaction: copy the elements with a non-zero flag to their slot,
+	given the exclusive prefix sum of the flags (see ComputeLib::compact).
+
+	Defines: T (element type).
+*/
+#version 450 core
+
+#define GROUP_SIZE 256
+
+layout (local_size_x = GROUP_SIZE) in;
+
+layout (std430, binding = 0) readonly  buffer Src     { T src[]; };
+layout (std430, binding = 1) readonly  buffer Flags   { uint flags[]; };
+layout (std430, binding = 2) readonly  buffer Offsets { uint offsets[]; };
+layout (std430, binding = 3) writeonly buffer Dst     { T dst[]; };
+layout (std430, binding = 4) writeonly buffer Count   { uint count; };
+
+uniform uint n = 0;
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+	if (i >= n) {
+		return;
+	}
+	if (flags[i] != 0) {
+		dst[offsets[i]] = src[i];
+	}
+	if (i == n - 1) {
+		count = offsets[i] + flags[i];
+	}
+}
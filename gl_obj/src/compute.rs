@@ -0,0 +1,518 @@
+use super::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+// Invocations per work group, must match GROUP_SIZE in the shaders.
+const GROUP_SIZE: u32 = 256;
+
+// Maximum number of work groups in x (the minimum the GL spec guarantees).
+const MAX_GROUPS: u32 = 65535;
+
+/// Element type of the GPU primitives in `ComputeLib`: `f32`, `i32`, `u32`, `vec2` and `vec4`.
+/// Min and max of vectors are per component (e.g. the corners of a bounding box).
+pub trait ComputeScalar: Sized + Copy + Default + Debug + 'static {
+	/// GLSL type, e.g. "vec2".
+	const GLSL_TYPE: &'static str;
+
+	/// GLSL expressions for the lowest and highest value of a component.
+	const LOWEST: &'static str;
+	const HIGHEST: &'static str;
+
+	/// GLSL image type and swizzle to load an element from an image, e.g. "image2D", ".xy".
+	const IMAGE_TYPE: &'static str;
+	const SWIZZLE: &'static str;
+
+	/// CPU reference implementations of the operations.
+	fn add(a: Self, b: Self) -> Self;
+	fn min(a: Self, b: Self) -> Self;
+	fn max(a: Self, b: Self) -> Self;
+
+	/// The components, e.g. to compare results.
+	fn components(self) -> Vec<f64>;
+}
+
+macro_rules! compute_scalar {
+	($t:ty, $glsl:expr, $lowest:expr, $highest:expr, $image:expr, $swizzle:expr, add: $add:expr) => {
+		impl ComputeScalar for $t {
+			const GLSL_TYPE: &'static str = $glsl;
+			const LOWEST: &'static str = $lowest;
+			const HIGHEST: &'static str = $highest;
+			const IMAGE_TYPE: &'static str = $image;
+			const SWIZZLE: &'static str = $swizzle;
+
+			fn add(a: Self, b: Self) -> Self {
+				$add(a, b)
+			}
+
+			fn min(a: Self, b: Self) -> Self {
+				if b < a {
+					b
+				} else {
+					a
+				}
+			}
+
+			fn max(a: Self, b: Self) -> Self {
+				if b > a {
+					b
+				} else {
+					a
+				}
+			}
+
+			fn components(self) -> Vec<f64> {
+				vec![self as f64]
+			}
+		}
+	};
+	($t:ident, $glsl:expr, $image:expr, $swizzle:expr, $($i:tt),*) => {
+		impl ComputeScalar for $t {
+			const GLSL_TYPE: &'static str = $glsl;
+			const LOWEST: &'static str = "-3.402823466e+38";
+			const HIGHEST: &'static str = "3.402823466e+38";
+			const IMAGE_TYPE: &'static str = $image;
+			const SWIZZLE: &'static str = $swizzle;
+
+			fn add(a: Self, b: Self) -> Self {
+				$t($(a.$i + b.$i),*)
+			}
+
+			fn min(a: Self, b: Self) -> Self {
+				$t($(f32::min(a.$i, b.$i)),*)
+			}
+
+			fn max(a: Self, b: Self) -> Self {
+				$t($(f32::max(a.$i, b.$i)),*)
+			}
+
+			fn components(self) -> Vec<f64> {
+				vec![$(self.$i as f64),*]
+			}
+		}
+	};
+}
+
+compute_scalar!(f32, "float", "-3.402823466e+38", "3.402823466e+38", "image2D", ".x", add: |a, b| a + b);
+compute_scalar!(i32, "int", "(-2147483647 - 1)", "2147483647", "iimage2D", ".x", add: i32::wrapping_add);
+compute_scalar!(u32, "uint", "0u", "4294967295u", "uimage2D", ".x", add: u32::wrapping_add);
+compute_scalar!(vec2, "vec2", "image2D", ".xy", 0, 1);
+compute_scalar!(vec4, "vec4", "image2D", "", 0, 1, 2, 3);
+
+/// Binary operation for `ComputeLib::reduce`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReduceOp {
+	Sum,
+	Min,
+	Max,
+}
+
+impl ReduceOp {
+	/// Apply the operation on the CPU.
+	pub fn apply<T: ComputeScalar>(self, a: T, b: T) -> T {
+		match self {
+			ReduceOp::Sum => T::add(a, b),
+			ReduceOp::Min => T::min(a, b),
+			ReduceOp::Max => T::max(a, b),
+		}
+	}
+
+	// OP(a, b) and IDENTITY for reduce.glsl.
+	fn defines<T: ComputeScalar>(self) -> Vec<(&'static str, String)> {
+		let (op, identity) = match self {
+			ReduceOp::Sum => ("((a) + (b))", "0"),
+			ReduceOp::Min => ("min(a, b)", T::HIGHEST),
+			ReduceOp::Max => ("max(a, b)", T::LOWEST),
+		};
+		vec![("T", T::GLSL_TYPE.into()), ("OP(a, b)", op.into()), ("IDENTITY", format!("{}({})", T::GLSL_TYPE, identity))]
+	}
+}
+
+/// Data-parallel building blocks, implemented as compute shaders:
+/// reduction, exclusive prefix scan, stream compaction and radix sort.
+///
+/// Programs are compiled on first use, for each element type and operation.
+/// Temporary buffers are allocated per call. Inputs are limited to 65535 * 256 (about 16M) elements.
+///
+/// Each primitive has a CPU reference implementation (`cpu_reduce` etc.), `self_test` compares them.
+pub struct ComputeLib {
+	programs: RefCell<HashMap<String, Rc<Program>>>,
+}
+
+impl ComputeLib {
+	pub fn new() -> Self {
+		Self {
+			programs: RefCell::new(HashMap::new()),
+		}
+	}
+
+	/// Reduce the elements of `src` to a single value, e.g. their sum or minimum.
+	pub fn reduce<T: ComputeScalar>(&self, src: &Buffer<T>, op: ReduceOp) -> Result<T, String> {
+		let n = src.len();
+		if n == 0 {
+			return Err("reduce: empty buffer".into());
+		}
+		let partial = Buffer::new_uninit(groups(n)?, 0);
+		let p = self.program("reduce.glsl", &op.defines::<T>())?;
+		src.bind_base(gl::SHADER_STORAGE_BUFFER, 0);
+		partial.bind_base(gl::SHADER_STORAGE_BUFFER, 1);
+		run(&p, n)?;
+		self.reduce_partial(partial, groups(n)?, op)
+	}
+
+	/// Reduce the texels of a 2D texture, e.g. the bounding box of positions stored in an `Rg32f` texture.
+	pub fn reduce_texture<F>(&self, src: &Texture<F>, op: ReduceOp) -> Result<F::Texel, String>
+	where
+		F: PixelFormat,
+		F::Texel: ComputeScalar,
+	{
+		Ok(self.reduce_texture_to_buffer(src, op)?.get_data()[0])
+	}
+
+	/// Like `reduce_texture`, but leave the result on the GPU, in a buffer of length 1,
+	/// e.g. to read it back later with `AsyncReadback` instead of waiting for it.
+	pub fn reduce_texture_to_buffer<F>(&self, src: &Texture<F>, op: ReduceOp) -> Result<Buffer<F::Texel>, String>
+	where
+		F: PixelFormat,
+		F::Texel: ComputeScalar,
+	{
+		let n = src.num_texels() as u32;
+		if n == 0 {
+			return Err("reduce_texture: empty texture".into());
+		}
+		let mut defines = op.defines::<F::Texel>();
+		defines.push(("FROM_IMAGE", "1".into()));
+		defines.push(("IMAGE_TYPE", F::Texel::IMAGE_TYPE.into()));
+		defines.push(("FORMAT", image_format_name(F::INTERNAL_FORMAT).into()));
+		defines.push(("SWIZZLE", F::Texel::SWIZZLE.into()));
+		let partial = Buffer::new_uninit(groups(n)?, 0);
+		let p = self.program("reduce.glsl", &defines)?;
+		src.bind_image_unit(0, gl::READ_ONLY);
+		partial.bind_base(gl::SHADER_STORAGE_BUFFER, 1);
+		run(&p, n)?;
+		self.reduce_partial_to_buffer(partial, groups(n)?, op)
+	}
+
+	// Reduce the `n` partial results in `partial` until one is left, and read it back.
+	fn reduce_partial<T: ComputeScalar>(&self, partial: Buffer<T>, n: u32, op: ReduceOp) -> Result<T, String> {
+		Ok(self.reduce_partial_to_buffer(partial, n, op)?.get_data()[0])
+	}
+
+	// Reduce the `n` partial results in `partial` until one is left, in the returned buffer.
+	fn reduce_partial_to_buffer<T: ComputeScalar>(&self, mut partial: Buffer<T>, mut n: u32, op: ReduceOp) -> Result<Buffer<T>, String> {
+		let p = self.program("reduce.glsl", &op.defines::<T>())?;
+		while n > 1 {
+			let next = Buffer::new_uninit(groups(n)?, 0);
+			partial.bind_base(gl::SHADER_STORAGE_BUFFER, 0);
+			next.bind_base(gl::SHADER_STORAGE_BUFFER, 1);
+			run(&p, n)?;
+			partial = next;
+			n = groups(n)?;
+		}
+		Ok(partial)
+	}
+
+	/// Write the exclusive prefix sum of `src` to `dst`:
+	/// `dst[i] = src[0] + ... + src[i-1]`, `dst[0] = 0`.
+	pub fn exclusive_scan<T: ComputeScalar>(&self, src: &Buffer<T>, dst: &Buffer<T>) -> Result<(), String> {
+		if dst.len() < src.len() {
+			return Err(format!("exclusive_scan: destination too small ({} < {})", dst.len(), src.len()));
+		}
+		if src.len() == 0 {
+			return Ok(());
+		}
+		self.scan(src, dst, src.len())
+	}
+
+	// Scan each block, then (recursively) scan the block totals and add them to the blocks.
+	fn scan<T: ComputeScalar>(&self, src: &Buffer<T>, dst: &Buffer<T>, n: u32) -> Result<(), String> {
+		let defines = [("T", T::GLSL_TYPE.to_string())];
+		let groups = groups(n)?;
+		let sums = Buffer::<T>::new_uninit(groups, 0);
+		let p = self.program("scan.glsl", &defines)?;
+		src.bind_base(gl::SHADER_STORAGE_BUFFER, 0);
+		dst.bind_base(gl::SHADER_STORAGE_BUFFER, 1);
+		sums.bind_base(gl::SHADER_STORAGE_BUFFER, 2);
+		run(&p, n)?;
+
+		if groups > 1 {
+			let offsets = Buffer::new_uninit(groups, 0);
+			self.scan(&sums, &offsets, groups)?;
+			let p = self.program("scan_add.glsl", &defines)?;
+			offsets.bind_base(gl::SHADER_STORAGE_BUFFER, 0);
+			dst.bind_base(gl::SHADER_STORAGE_BUFFER, 1);
+			run(&p, n)?;
+		}
+		Ok(())
+	}
+
+	/// Copy the elements of `src` whose flag is 1 (flags must be 0 or 1) to the front of `dst`, in order,
+	/// and write their number to `count[0]`. Everything stays on the GPU, so that the count can feed
+	/// an indirect dispatch or draw (see `IndirectArgs`).
+	pub fn compact<T: ComputeScalar>(&self, src: &Buffer<T>, flags: &Buffer<u32>, dst: &Buffer<T>, count: &Buffer<u32>) -> Result<(), String> {
+		let n = src.len();
+		if flags.len() < n || dst.len() < n || count.len() == 0 {
+			return Err(format!(
+				"compact: buffers too small: {} elements, {} flags, destination {}, count {}",
+				n,
+				flags.len(),
+				dst.len(),
+				count.len()
+			));
+		}
+		if n == 0 {
+			count.clear(0);
+			return Ok(());
+		}
+		let offsets = Buffer::new_uninit(n, 0);
+		self.scan(flags, &offsets, n)?;
+		let p = self.program("compact.glsl", &[("T", T::GLSL_TYPE.to_string())])?;
+		src.bind_base(gl::SHADER_STORAGE_BUFFER, 0);
+		flags.bind_base(gl::SHADER_STORAGE_BUFFER, 1);
+		offsets.bind_base(gl::SHADER_STORAGE_BUFFER, 2);
+		dst.bind_base(gl::SHADER_STORAGE_BUFFER, 3);
+		count.bind_base(gl::SHADER_STORAGE_BUFFER, 4);
+		run(&p, n)
+	}
+
+	/// Sort `keys` in place, and permute `values` along, considering only the lowest `bits` bits of the keys
+	/// (e.g. a cell index that fits in 16 bits). The sort is stable.
+	pub fn sort_pairs(&self, keys: &Buffer<u32>, values: &Buffer<u32>, bits: u32) -> Result<(), String> {
+		let n = keys.len();
+		if values.len() != n {
+			return Err(format!("sort_pairs: {} keys but {} values", n, values.len()));
+		}
+		if n == 0 || bits == 0 {
+			return Ok(());
+		}
+		let flags = Buffer::<u32>::new_uninit(n, 0);
+		let offsets = Buffer::new_uninit(n, 0);
+		let (keys2, values2) = (Buffer::new_uninit(n, 0), Buffer::new_uninit(n, 0));
+		let p_flags = self.program("radix.glsl", &[])?;
+		let p_scatter = self.program("radix.glsl", &[("SCATTER", "1".into())])?;
+
+		let (mut src, mut dst) = ((keys, values), (&keys2, &values2));
+		for bit in 0..bits.min(32) {
+			p_flags.uniform::<u32>("bit").set(bit);
+			src.0.bind_base(gl::SHADER_STORAGE_BUFFER, 0);
+			flags.bind_base(gl::SHADER_STORAGE_BUFFER, 2);
+			run(&p_flags, n)?;
+
+			self.scan(&flags, &offsets, n)?;
+
+			p_scatter.uniform::<u32>("bit").set(bit);
+			src.0.bind_base(gl::SHADER_STORAGE_BUFFER, 0);
+			src.1.bind_base(gl::SHADER_STORAGE_BUFFER, 1);
+			offsets.bind_base(gl::SHADER_STORAGE_BUFFER, 2);
+			dst.0.bind_base(gl::SHADER_STORAGE_BUFFER, 3);
+			dst.1.bind_base(gl::SHADER_STORAGE_BUFFER, 4);
+			run(&p_scatter, n)?;
+
+			std::mem::swap(&mut src, &mut dst);
+		}
+		// After an odd number of passes, the result is in the temporaries.
+		if src.0.handle() != keys.handle() {
+			keys.copy_from(src.0, 0, 0, n);
+			values.copy_from(src.1, 0, 0, n);
+		}
+		Ok(())
+	}
+
+	/// Run every primitive on `n` pseudo-random elements, and compare with the CPU reference implementations.
+	pub fn self_test(&self, n: u32) -> Result<(), String> {
+		let mut seed = 1u32;
+		let mut rand = move || {
+			seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+			seed
+		};
+		let ints: Vec<u32> = (0..n).map(|_| rand() >> 12).collect();
+		let floats: Vec<f32> = (0..n).map(|_| (rand() >> 8) as f32 / (1 << 24) as f32 - 0.5).collect();
+		let flags: Vec<u32> = (0..n).map(|_| rand() >> 31).collect();
+		let side = 128;
+		let points: Vec<vec2> = (0..side * side).map(|_| vec2(rand() as f32 / 1e9, -(rand() as f32) / 1e9)).collect();
+
+		let ints_buf = Buffer::new(&ints, 0);
+		let floats_buf = Buffer::new(&floats, 0);
+		for &op in &[ReduceOp::Sum, ReduceOp::Min, ReduceOp::Max] {
+			check(&format!("reduce {:?} u32", op), &[self.reduce(&ints_buf, op)?], &[cpu_reduce(&ints, op).unwrap()], 0.0)?;
+			check(&format!("reduce {:?} f32", op), &[self.reduce(&floats_buf, op)?], &[cpu_reduce(&floats, op).unwrap()], 1e-3)?;
+			let tex = Texture::<Rg32f>::from_data2d(uvec2(side, side), &points);
+			check(
+				&format!("reduce_texture {:?} vec2", op),
+				&[self.reduce_texture(&tex, op)?],
+				&[cpu_reduce(&points, op).unwrap()],
+				1e-3,
+			)?;
+		}
+
+		let scanned = Buffer::new_uninit(n, 0);
+		self.exclusive_scan(&ints_buf, &scanned)?;
+		check("exclusive_scan u32", &scanned.get_data(), &cpu_exclusive_scan(&ints), 0.0)?;
+
+		let compacted = Buffer::new_uninit(n, 0);
+		let count = Buffer::new(&[0u32], 0);
+		self.compact(&floats_buf, &Buffer::new(&flags, 0), &compacted, &count)?;
+		let want = cpu_compact(&floats, &flags);
+		check("compact count", &count.get_data(), &[want.len() as u32], 0.0)?;
+		check("compact f32", &compacted.get_data()[..want.len()], &want, 0.0)?;
+
+		let values: Vec<u32> = (0..n).collect();
+		let (keys_buf, values_buf) = (Buffer::new(&ints, 0), Buffer::new(&values, 0));
+		self.sort_pairs(&keys_buf, &values_buf, 32)?;
+		let (mut want_keys, mut want_values) = (ints, values);
+		cpu_sort_pairs(&mut want_keys, &mut want_values, 32);
+		check("sort_pairs keys", &keys_buf.get_data(), &want_keys, 0.0)?;
+		check("sort_pairs values", &values_buf.get_data(), &want_values, 0.0)
+	}
+
+	// The compute program `file` with given #defines, compiled on first use.
+	fn program(&self, file: &str, defines: &[(&str, String)]) -> Result<Rc<Program>, String> {
+		let key = format!("{} {:?}", file, defines);
+		if let Some(p) = self.programs.borrow().get(&key) {
+			return Ok(p.clone());
+		}
+		let mut pp = Preprocessor::new(
+			EmbeddedFs::new()
+				.file("compact.glsl", include_str!("compact.glsl"))
+				.file("radix.glsl", include_str!("radix.glsl"))
+				.file("reduce.glsl", include_str!("reduce.glsl"))
+				.file("scan.glsl", include_str!("scan.glsl"))
+				.file("scan_add.glsl", include_str!("scan_add.glsl")),
+		);
+		for (name, value) in defines {
+			pp = pp.define(name, value);
+		}
+		let shader = pp.shader(gl::COMPUTE_SHADER, file).map_err(|e| e.to_string())?;
+		let p = Rc::new(Program::new(&[shader]).map_err(|e| e.to_string())?);
+		self.programs.borrow_mut().insert(key, p.clone());
+		Ok(p)
+	}
+}
+
+impl Default for ComputeLib {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+// Number of work groups for `n` invocations.
+fn groups(n: u32) -> Result<u32, String> {
+	let groups = n.div_ceil(GROUP_SIZE);
+	if groups > MAX_GROUPS {
+		return Err(format!("{} elements is too many, the maximum is {}", n, MAX_GROUPS * GROUP_SIZE));
+	}
+	Ok(groups)
+}
+
+// Run `p` over `n` elements (uniform `n`, rounded up to whole work groups).
+fn run(p: &Program, n: u32) -> Result<(), String> {
+	p.uniform::<u32>("n").set(n);
+	p.compute_and_sync(uvec3(groups(n)? * GROUP_SIZE, 1, 1))
+}
+
+// Compare GPU results with CPU reference results, up to a relative tolerance.
+fn check<T: ComputeScalar>(what: &str, got: &[T], want: &[T], tolerance: f64) -> Result<(), String> {
+	if got.len() != want.len() {
+		return Err(format!("{}: got {} elements, want {}", what, got.len(), want.len()));
+	}
+	for (i, (g, w)) in got.iter().zip(want).enumerate() {
+		for (g, w) in g.components().into_iter().zip(w.components()) {
+			if (g - w).abs() > tolerance * w.abs().max(1.0) {
+				return Err(format!("{}: element {}: got {:?}, want {:?}", what, i, got[i], want[i]));
+			}
+		}
+	}
+	Ok(())
+}
+
+/// CPU reference implementation of `ComputeLib::reduce`. `None` if `data` is empty.
+pub fn cpu_reduce<T: ComputeScalar>(data: &[T], op: ReduceOp) -> Option<T> {
+	data.iter().copied().reduce(|a, b| op.apply(a, b))
+}
+
+/// CPU reference implementation of `ComputeLib::exclusive_scan`.
+pub fn cpu_exclusive_scan<T: ComputeScalar>(data: &[T]) -> Vec<T> {
+	let mut sum = T::default();
+	data.iter()
+		.map(|&x| {
+			let prev = sum;
+			sum = T::add(sum, x);
+			prev
+		})
+		.collect()
+}
+
+/// CPU reference implementation of `ComputeLib::compact`.
+pub fn cpu_compact<T: ComputeScalar>(data: &[T], flags: &[u32]) -> Vec<T> {
+	data.iter().zip(flags).filter(|(_, &f)| f != 0).map(|(&x, _)| x).collect()
+}
+
+/// CPU reference implementation of `ComputeLib::sort_pairs`.
+pub fn cpu_sort_pairs(keys: &mut [u32], values: &mut [u32], bits: u32) {
+	let mask = if bits >= 32 { !0 } else { (1 << bits) - 1 };
+	let mut pairs: Vec<(u32, u32)> = keys.iter().copied().zip(values.iter().copied()).collect();
+	pairs.sort_by_key(|&(k, _)| k & mask);
+	for (i, (k, v)) in pairs.into_iter().enumerate() {
+		keys[i] = k;
+		values[i] = v;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn reference_reduce() {
+		let points = [vec2(1.0, -1.0), vec2(-2.0, 5.0), vec2(3.0, 0.0)];
+		// Min and Max are per component.
+		assert_eq!(cpu_reduce(&points, ReduceOp::Min).unwrap().components(), vec![-2.0, -1.0]);
+		assert_eq!(cpu_reduce(&points, ReduceOp::Max).unwrap().components(), vec![3.0, 5.0]);
+		assert_eq!(cpu_reduce(&points, ReduceOp::Sum).unwrap().components(), vec![2.0, 4.0]);
+		assert_eq!(cpu_reduce(&[7u32, 2, 9], ReduceOp::Min), Some(2));
+		assert_eq!(cpu_reduce::<u32>(&[], ReduceOp::Sum), None);
+	}
+
+	#[test]
+	fn reference_exclusive_scan() {
+		assert_eq!(cpu_exclusive_scan(&[3u32, 1, 4, 1, 5]), vec![0, 3, 4, 8, 9]);
+		assert_eq!(cpu_exclusive_scan(&[0.5f32, 0.25]), vec![0.0, 0.5]);
+		assert_eq!(cpu_exclusive_scan::<u32>(&[]), Vec::<u32>::new());
+	}
+
+	#[test]
+	fn reference_compact() {
+		// Keeps the flagged elements (any non-zero flag), in their original order.
+		let data = [1.0f32, 2.0, 3.0, 4.0, 5.0];
+		assert_eq!(cpu_compact(&data, &[0, 1, 7, 0, 1]), vec![2.0, 3.0, 5.0]);
+		assert_eq!(cpu_compact(&data, &[0; 5]), Vec::<f32>::new());
+	}
+
+	#[test]
+	fn reference_sort_pairs() {
+		// Sorting on the low 4 bits only, stable: equal keys keep their order.
+		let mut keys = [0x13, 0x02, 0x21, 0x12, 0x03];
+		let mut values = [0, 1, 2, 3, 4];
+		cpu_sort_pairs(&mut keys, &mut values, 4);
+		assert_eq!(keys, [0x21, 0x02, 0x12, 0x13, 0x03]);
+		assert_eq!(values, [2, 1, 3, 0, 4]);
+
+		let mut keys = [0x13, 0x02, 0x21, 0x12, 0x03];
+		let mut values = [0, 1, 2, 3, 4];
+		cpu_sort_pairs(&mut keys, &mut values, 32);
+		assert_eq!(keys, [0x02, 0x03, 0x12, 0x13, 0x21]);
+		assert_eq!(values, [1, 4, 3, 0, 2]);
+	}
+
+	// Compares every GPU primitive with its CPU reference.
+	// Needs OpenGL 4.5 (a GPU, or OSMesa): `cargo test -p gl_obj -- --ignored`.
+	#[test]
+	#[ignore]
+	fn gpu_matches_reference() {
+		let _context = gl_win::init_gl_headless(1, 1).unwrap_or_else(|e| panic!("{}", e));
+		let result = ComputeLib::new().self_test(1 << 20);
+		report_live_objects();
+		result.unwrap_or_else(|e| panic!("{}", e));
+	}
+}
@@ -0,0 +1,47 @@
+use gl_safe::*;
+use std::time::Duration;
+
+/// A fence sync object, signaled once the GPU has finished all commands issued before it.
+pub struct Fence(GLsync);
+
+impl Fence {
+	/// Insert a fence into the GL command stream.
+	/// http://docs.gl/gl4/glFenceSync
+	pub fn new() -> Self {
+		Self(glFenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0))
+	}
+
+	/// Returns true if all commands before the fence have completed.
+	/// Does not block.
+	pub fn is_signaled(&self) -> bool {
+		self.wait_timeout(Duration::from_nanos(0))
+	}
+
+	/// Block until all commands before the fence have completed,
+	/// or `timeout` has passed. Returns true if the fence was signaled.
+	/// http://docs.gl/gl4/glClientWaitSync
+	pub fn wait_timeout(&self, timeout: Duration) -> bool {
+		match glClientWaitSync(self.0, gl::SYNC_FLUSH_COMMANDS_BIT, timeout.as_nanos() as u64) {
+			gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => true,
+			gl::TIMEOUT_EXPIRED => false,
+			_ => panic!("glClientWaitSync failed"),
+		}
+	}
+
+	/// Block until all commands before the fence have completed.
+	pub fn wait(&self) {
+		while !self.wait_timeout(Duration::from_millis(100)) {}
+	}
+}
+
+impl Default for Fence {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Drop for Fence {
+	fn drop(&mut self) {
+		glDeleteSync(self.0)
+	}
+}
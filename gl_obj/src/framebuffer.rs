@@ -0,0 +1,174 @@
+use super::*;
+use gl_safe::*;
+
+/// A framebuffer object, for rendering into textures (render-to-texture)
+/// at a resolution independent of the window.
+pub struct Framebuffer {
+	handle: GLuint,
+	size: uvec2,
+}
+
+impl Framebuffer {
+	/// Create a framebuffer object without attachments.
+	/// http://docs.gl/gl4/glCreateFramebuffers
+	#[track_caller]
+	pub fn create() -> Self {
+		let handle = glCreateFramebuffer();
+		registry::track("Framebuffer", handle);
+		Self { handle, size: uvec2(0, 0) }
+	}
+
+	/// A complete framebuffer rendering into `color` (attachment 0).
+	#[track_caller]
+	pub fn new<F: PixelFormat>(color: &Texture<F>) -> Result<Self, String> {
+		Self::create().texture(gl::COLOR_ATTACHMENT0, color, 0).check()
+	}
+
+	/// The default framebuffer (i.e. the window), of given size.
+	/// Useful as the last target in a chain of render passes, or for screenshots.
+	pub fn window(size: uvec2) -> Self {
+		Self { handle: 0, size }
+	}
+
+	/// Attach a level of a texture, e.g. to COLOR_ATTACHMENT0.
+	/// The framebuffer size becomes the texture size.
+	/// http://docs.gl/gl4/glFramebufferTexture
+	pub fn texture<F: PixelFormat>(mut self, attachment: GLenum, texture: &Texture<F>, level: i32) -> Self {
+		glNamedFramebufferTexture(self.handle, attachment, texture.handle(), level);
+		let size = texture.size();
+		self.size = uvec2(size.0, size.1.max(1));
+		self
+	}
+
+	/// Attach a renderbuffer, e.g. to DEPTH_ATTACHMENT.
+	/// http://docs.gl/gl4/glFramebufferRenderbuffer
+	pub fn renderbuffer(mut self, attachment: GLenum, renderbuffer: &Renderbuffer) -> Self {
+		glNamedFramebufferRenderbuffer(self.handle, attachment, renderbuffer.handle());
+		self.size = renderbuffer.size();
+		self
+	}
+
+	/// Specifies the color attachments to be drawn into.
+	/// http://docs.gl/gl4/glDrawBuffers
+	pub fn draw_buffers(self, bufs: &[GLenum]) -> Self {
+		glNamedFramebufferDrawBuffers(self.handle, bufs);
+		self
+	}
+
+	/// Check framebuffer completeness.
+	/// http://docs.gl/gl4/glCheckFramebufferStatus
+	pub fn check(self) -> Result<Self, String> {
+		match glCheckNamedFramebufferStatus(self.handle, gl::FRAMEBUFFER) {
+			gl::FRAMEBUFFER_COMPLETE => Ok(self),
+			status => Err(format!("framebuffer {} incomplete: {}", self.handle, status_str(status))),
+		}
+	}
+
+	pub fn size(&self) -> uvec2 {
+		self.size
+	}
+
+	pub fn handle(&self) -> GLuint {
+		self.handle
+	}
+
+	/// Render into this framebuffer from now on,
+	/// and set the viewport to cover all of it.
+	pub fn bind(&self) {
+		glBindFramebuffer(gl::FRAMEBUFFER, self.handle);
+		glViewport(0, 0, self.size.0 as i32, self.size.1 as i32);
+	}
+
+	/// Clear a color attachment (draw buffer index `drawbuffer`) to `color`.
+	/// http://docs.gl/gl4/glClearBuffer
+	pub fn clear_color(&self, drawbuffer: i32, color: vec4) {
+		glClearNamedFramebufferfv(self.handle, gl::COLOR, drawbuffer, &[color.0, color.1, color.2, color.3]);
+	}
+
+	/// Copy the color contents to another framebuffer, scaling to fit.
+	/// http://docs.gl/gl4/glBlitFramebuffer
+	pub fn blit_to(&self, dst: &Framebuffer, filter: GLenum) {
+		let (s, d) = (self.size, dst.size);
+		glBlitNamedFramebuffer(
+			self.handle,
+			dst.handle,
+			[0, 0, s.0 as i32, s.1 as i32],
+			[0, 0, d.0 as i32, d.1 as i32],
+			gl::COLOR_BUFFER_BIT,
+			filter,
+		);
+	}
+
+	/// Read back the pixels of color attachment `attachment` (e.g. COLOR_ATTACHMENT0, or BACK for the window),
+	/// in pixel `format` and `typ`. Rows are ordered bottom to top.
+	/// Blocks until rendering is finished.
+	/// http://docs.gl/gl4/glReadPixels
+	pub fn read_pixels<T>(&self, attachment: GLenum, format: GLenum, typ: GLenum) -> Vec<T>
+	where
+		T: Sized + Copy + Default + 'static,
+	{
+		let (w, h) = (self.size.0 as i32, self.size.1 as i32);
+		let mut pixels = vec![T::default(); (w * h) as usize];
+		glNamedFramebufferReadBuffer(self.handle, attachment);
+		glBindFramebuffer(gl::READ_FRAMEBUFFER, self.handle);
+		glReadnPixels(0, 0, w, h, format, typ, &mut pixels);
+		pixels
+	}
+}
+
+impl Drop for Framebuffer {
+	fn drop(&mut self) {
+		if self.handle != 0 {
+			registry::untrack("Framebuffer", self.handle);
+			glDeleteFramebuffer(self.handle)
+		}
+	}
+}
+
+/// A renderbuffer object: an image for use as a framebuffer attachment
+/// when it will not be sampled as a texture (e.g. a depth buffer).
+pub struct Renderbuffer {
+	handle: GLuint,
+	size: uvec2,
+}
+
+impl Renderbuffer {
+	/// Create a renderbuffer with storage of given format and size.
+	/// http://docs.gl/gl4/glRenderbufferStorage
+	#[track_caller]
+	pub fn new(internalformat: GLenum, size: uvec2) -> Self {
+		let handle = glCreateRenderbuffer();
+		registry::track("Renderbuffer", handle);
+		glNamedRenderbufferStorage(handle, internalformat, size.0 as i32, size.1 as i32);
+		Self { handle, size }
+	}
+
+	pub fn size(&self) -> uvec2 {
+		self.size
+	}
+
+	pub fn handle(&self) -> GLuint {
+		self.handle
+	}
+}
+
+impl Drop for Renderbuffer {
+	fn drop(&mut self) {
+		registry::untrack("Renderbuffer", self.handle);
+		glDeleteRenderbuffer(self.handle)
+	}
+}
+
+fn status_str(status: GLenum) -> String {
+	match status {
+		gl::FRAMEBUFFER_UNDEFINED => "FRAMEBUFFER_UNDEFINED".into(),
+		gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => "FRAMEBUFFER_INCOMPLETE_ATTACHMENT".into(),
+		gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => "FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT".into(),
+		gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => "FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER".into(),
+		gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => "FRAMEBUFFER_INCOMPLETE_READ_BUFFER".into(),
+		gl::FRAMEBUFFER_UNSUPPORTED => "FRAMEBUFFER_UNSUPPORTED".into(),
+		gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "FRAMEBUFFER_INCOMPLETE_MULTISAMPLE".into(),
+		gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => "FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS".into(),
+		x => format!("status {}", x),
+	}
+}
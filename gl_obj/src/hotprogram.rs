@@ -0,0 +1,114 @@
+use super::*;
+use gl_safe::*;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+// Files a program was built from, with their modification times at the time.
+type Watched = Vec<(String, Option<SystemTime>)>;
+
+/// A program built from shader files that are watched for changes, for editing shaders while the application runs.
+///
+/// `poll` checks the modification times of the files (and everything they include),
+/// and rebuilds the program when one has changed. If compilation or linking fails,
+/// the error is printed and the previous program keeps running.
+///
+/// Only files on disk can be watched (see `DiskFs`), with embedded files this is just a `Program`.
+pub struct HotProgram {
+	program: Program,
+	preprocessor: Rc<Preprocessor>,
+	stages: Vec<(GLenum, String)>,
+	watched: Watched,
+	last_poll: Instant,
+}
+
+impl HotProgram {
+	/// Build a program from shader files, e.g. `&[(VERTEX_SHADER, "texture.vert"), (FRAGMENT_SHADER, "draw.frag")]`.
+	pub fn new(preprocessor: Rc<Preprocessor>, stages: &[(GLenum, &str)]) -> Result<Self, ShaderError> {
+		let stages: Vec<(GLenum, String)> = stages.iter().map(|&(typ, path)| (typ, path.to_string())).collect();
+		let (program, watched) = Self::build(&preprocessor, &stages)?;
+		Ok(Self {
+			program,
+			preprocessor,
+			stages,
+			watched,
+			last_poll: Instant::now(),
+		})
+	}
+
+	/// Build a compute program from a single file.
+	pub fn compute(preprocessor: Rc<Preprocessor>, path: &str) -> Result<Self, ShaderError> {
+		Self::new(preprocessor, &[(gl::COMPUTE_SHADER, path)])
+	}
+
+	/// The current program.
+	pub fn program(&self) -> &Program {
+		&self.program
+	}
+
+	/// Rebuild the program if any of its files changed since the last build.
+	/// Files are checked at most a few times per second, so this can be called every frame.
+	///
+	/// Returns true if the program was replaced. Uniform handles and attribute locations
+	/// then need to be resolved again, and uniform values set again.
+	pub fn poll(&mut self) -> bool {
+		if self.last_poll.elapsed() < Duration::from_millis(250) {
+			return false;
+		}
+		self.last_poll = Instant::now();
+
+		if self.watched.iter().all(|(path, time)| self.preprocessor.modified(path) == *time) {
+			return false;
+		}
+		match Self::build(&self.preprocessor, &self.stages) {
+			Ok((program, watched)) => {
+				self.program = program;
+				self.watched = watched;
+				println!("reloaded {}", self.name());
+				true
+			}
+			Err(e) => {
+				// Don't retry until the files change again.
+				self.watched = self.watched.iter().map(|(path, _)| (path.clone(), self.preprocessor.modified(path))).collect();
+				eprintln!("{}", e);
+				false
+			}
+		}
+	}
+
+	/// The file names of the shader stages, for messages.
+	pub fn name(&self) -> String {
+		stage_names(&self.stages)
+	}
+
+	// Compile and link all stages. Returns the program and the modification times of all files used.
+	fn build(preprocessor: &Preprocessor, stages: &[(GLenum, String)]) -> Result<(Program, Watched), ShaderError> {
+		let mut watched = Vec::new();
+		let mut shaders = Vec::new();
+		for (typ, path) in stages {
+			let src = preprocessor.process(path).map_err(|e| ShaderError::other(*typ, path, e))?;
+			watched.extend(src.files().into_iter().map(|f| (f.to_string(), preprocessor.modified(f))));
+			shaders.push(Shader::from_source(*typ, &src)?);
+		}
+		let program = Program::new(&shaders).map_err(|e| ShaderError {
+			source_name: stage_names(stages),
+			..e
+		})?;
+		Ok((program, watched))
+	}
+}
+
+// e.g. "texture.vert+draw.frag"
+fn stage_names(stages: &[(GLenum, String)]) -> String {
+	stages.iter().map(|(_, path)| path.as_str()).collect::<Vec<_>>().join("+")
+}
+
+impl Deref for HotProgram {
+	type Target = Program;
+
+	fn deref(&self) -> &Program {
+		&self.program
+	}
+}
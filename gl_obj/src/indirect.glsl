@@ -0,0 +1,27 @@
+/*
+	Turn a count produced on the GPU (e.g. by an atomic counter) into
+	indirect dispatch and draw commands (see IndirectArgs).
+*/
+#version 450 core
+
+layout (local_size_x = 1) in;
+
+layout (std430, binding = 0) readonly  buffer Counts   { uint counts[]; };
+layout (std430, binding = 1) writeonly buffer Dispatch { uint dispatch[3]; }; // DispatchIndirectCommand
+layout (std430, binding = 2) writeonly buffer Draw     { uint draw[4]; };     // DrawArraysIndirectCommand
+
+uniform uint count_index = 0;
+uniform uint group_size = 64;
+
+void main() {
+	uint n = counts[count_index];
+
+	dispatch[0] = (n + group_size - 1) / group_size;
+	dispatch[1] = 1;
+	dispatch[2] = 1;
+
+	draw[0] = n; // count
+	draw[1] = 1; // instance count
+	draw[2] = 0; // first
+	draw[3] = 0; // base instance
+}
@@ -0,0 +1,136 @@
+use super::*;
+
+/// Arguments of an indirect compute dispatch, as read from a DISPATCH_INDIRECT_BUFFER.
+/// See `Program::compute_indirect_and_sync`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DispatchIndirectCommand {
+	pub num_groups_x: u32,
+	pub num_groups_y: u32,
+	pub num_groups_z: u32,
+}
+
+/// Arguments of an indirect draw, as read from a DRAW_INDIRECT_BUFFER.
+/// See `VertexArray::draw_arrays_indirect`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DrawArraysIndirectCommand {
+	pub count: u32,
+	pub instance_count: u32,
+	pub first: u32,
+	pub base_instance: u32,
+}
+
+/// A buffer of `atomic_uint` counters, e.g. for counting surviving particles in a compute pass.
+///
+/// Declared in GLSL as e.g. `layout(binding = 0, offset = 0) uniform atomic_uint alive;`
+/// (the offset is in bytes, 4 per counter). The same buffer can be bound as a storage block
+/// to read the counts in a later pass (see `IndirectArgs`).
+pub struct AtomicCounterBuffer {
+	buffer: Buffer<u32>,
+}
+
+impl AtomicCounterBuffer {
+	/// `len` counters, initially zero.
+	#[track_caller]
+	pub fn new(len: u32) -> Self {
+		Self {
+			buffer: Buffer::new(&vec![0; len as usize], gl::DYNAMIC_STORAGE_BIT),
+		}
+	}
+
+	/// Number of counters.
+	pub fn len(&self) -> u32 {
+		self.buffer.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.buffer.len() == 0
+	}
+
+	pub fn buffer(&self) -> &Buffer<u32> {
+		&self.buffer
+	}
+
+	/// Bind to atomic counter buffer binding point `binding`.
+	pub fn bind(&self, binding: u32) {
+		self.buffer.bind_base(gl::ATOMIC_COUNTER_BUFFER, binding)
+	}
+
+	/// Set all counters to zero.
+	pub fn reset(&self) {
+		self.buffer.clear(0)
+	}
+
+	/// Set counter `index` to `value`.
+	pub fn set(&self, index: u32, value: u32) {
+		self.buffer.sub_data(index, &[value])
+	}
+
+	/// Read back the counters. Blocks until the GPU is done writing them.
+	pub fn get(&self) -> Vec<u32> {
+		self.buffer.get_data()
+	}
+}
+
+/// Turns a count produced on the GPU into the arguments for an indirect dispatch over that many invocations,
+/// and an indirect draw of that many vertices, without a round-trip to the CPU. E.g. to process and draw
+/// only the particles that survived a compaction pass:
+///
+/// ```ignore
+/// counters.reset();
+/// counters.bind(0);
+/// compact.compute_and_sync(size)?;                    // atomicCounterIncrement for each survivor
+/// args.update(&counters, 0, 64)?;                      // work groups of 64
+/// step.compute_indirect_and_sync(args.dispatch(), 0)?; // shader returns early if gl_GlobalInvocationID.x >= count
+/// vao.draw_arrays_indirect(POINTS, args.draw(), 0);
+/// ```
+pub struct IndirectArgs {
+	program: Program,
+	dispatch: Buffer<DispatchIndirectCommand>,
+	draw: Buffer<DrawArraysIndirectCommand>,
+}
+
+impl IndirectArgs {
+	#[track_caller]
+	pub fn new() -> Self {
+		let program = Shader::new_comp(include_str!("indirect.glsl"))
+			.and_then(|s| Program::new(&[s]))
+			.unwrap_or_else(|e| panic!("{}", e));
+		Self {
+			program,
+			dispatch: Buffer::new(&[DispatchIndirectCommand::default()], 0),
+			draw: Buffer::new(&[DrawArraysIndirectCommand::default()], 0),
+		}
+	}
+
+	/// Compute the dispatch and draw commands from counter `index` in `counters`,
+	/// for a compute shader with `group_size` invocations per work group.
+	pub fn update(&self, counters: &AtomicCounterBuffer, index: u32, group_size: u32) -> Result<(), String> {
+		if index >= counters.len() {
+			return Err(format!("IndirectArgs::update: counter {} out of bounds (len {})", index, counters.len()));
+		}
+		self.program.uniform::<u32>("count_index").set(index);
+		self.program.uniform::<u32>("group_size").set(group_size.max(1));
+		counters.buffer().bind_base(gl::SHADER_STORAGE_BUFFER, 0);
+		self.dispatch.bind_base(gl::SHADER_STORAGE_BUFFER, 1);
+		self.draw.bind_base(gl::SHADER_STORAGE_BUFFER, 2);
+		self.program.compute_and_sync(uvec3(1, 1, 1))
+	}
+
+	/// The dispatch command computed by `update`.
+	pub fn dispatch(&self) -> &Buffer<DispatchIndirectCommand> {
+		&self.dispatch
+	}
+
+	/// The draw command computed by `update`.
+	pub fn draw(&self) -> &Buffer<DrawArraysIndirectCommand> {
+		&self.draw
+	}
+}
+
+impl Default for IndirectArgs {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,753 @@
+use super::*;
+use gl_safe::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// Everything a linked program declares: active uniforms, blocks and images.
+/// Obtained with `Program::info`.
+#[derive(Clone, Debug)]
+pub struct ProgramInfo {
+	/// Uniforms in the default block (i.e. not in a uniform block), including samplers and images.
+	pub uniforms: Vec<UniformInfo>,
+	pub uniform_blocks: Vec<BlockInfo>,
+	pub storage_blocks: Vec<BlockInfo>,
+	/// Vertex shader inputs (excluding built-ins like gl_VertexID).
+	pub attributes: Vec<AttribInfo>,
+	/// Image uniforms, with the format and access qualifiers from the shader source.
+	pub images: Vec<ImageInfo>,
+	/// Local work group size, for compute programs.
+	pub work_group_size: Option<uvec3>,
+}
+
+/// An active uniform variable.
+#[derive(Clone, Debug)]
+pub struct UniformInfo {
+	pub name: String,
+	/// E.g. FLOAT_VEC2, IMAGE_2D, see `glsl_type_name`.
+	pub typ: GLenum,
+	/// Number of array elements, 1 for non-arrays.
+	pub array_size: i32,
+	pub location: i32,
+	/// Current value (i.e. the initializer right after linking).
+	pub value: UniformValue,
+}
+
+/// Value of a uniform, one element per component (per array element).
+/// Booleans, samplers and images are reported as `Int`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UniformValue {
+	Float(Vec<f32>),
+	Double(Vec<f64>),
+	Int(Vec<i32>),
+	Uint(Vec<u32>),
+}
+
+/// An active vertex shader input.
+#[derive(Clone, Debug)]
+pub struct AttribInfo {
+	pub name: String,
+	/// E.g. FLOAT_VEC2, see `glsl_type_name`.
+	pub typ: GLenum,
+	pub location: u32,
+}
+
+/// An active uniform block or shader storage block.
+#[derive(Clone, Debug)]
+pub struct BlockInfo {
+	pub name: String,
+	pub index: u32,
+	pub binding: u32,
+	/// Minimum buffer size in bytes (excluding a trailing runtime-sized array).
+	pub data_size: u32,
+	pub members: Vec<BlockMember>,
+}
+
+/// An active variable in a uniform or shader storage block.
+#[derive(Clone, Debug)]
+pub struct BlockMember {
+	/// E.g. "sun_pos", "attractors[0].pos".
+	pub name: String,
+	/// E.g. FLOAT_VEC2, see `glsl_type_name`.
+	pub typ: GLenum,
+	/// Byte offset from the start of the buffer.
+	pub offset: u32,
+	/// Distance between array elements in bytes, 0 for non-arrays.
+	pub array_stride: u32,
+	/// For members of a top-level array in a storage block (e.g. `Attractor attractors[];`):
+	/// its number of elements (0 if runtime-sized) and its stride.
+	pub top_level_array: Option<(u32, u32)>,
+}
+
+/// An active image uniform.
+#[derive(Clone, Debug)]
+pub struct ImageInfo {
+	pub name: String,
+	pub unit: u32,
+	/// E.g. IMAGE_2D, UNSIGNED_INT_IMAGE_2D.
+	pub typ: GLenum,
+	/// Format layout qualifier (e.g. RG32F), if declared.
+	pub format: Option<GLenum>,
+	/// READ_ONLY, WRITE_ONLY or READ_WRITE, from the memory qualifiers.
+	pub access: GLenum,
+}
+
+impl ProgramInfo {
+	/// Query the active resources of a linked program.
+	pub fn new(program: GLuint) -> Self {
+		registry::check_live("Program", program, "ProgramInfo::new");
+		let shaders = glGetAttachedShaders(program);
+		let is_compute = shaders.iter().any(|&s| glGetShaderiv(s, gl::SHADER_TYPE) == gl::COMPUTE_SHADER as i32);
+		let work_group_size = if is_compute {
+			let s = glGetProgramiv(program, gl::COMPUTE_WORK_GROUP_SIZE, 3);
+			Some(uvec3(s[0] as u32, s[1] as u32, s[2] as u32))
+		} else {
+			None
+		};
+
+		let uniforms = query_uniforms(program);
+		let has_vertex_shader = shaders.iter().any(|&s| glGetShaderiv(s, gl::SHADER_TYPE) == gl::VERTEX_SHADER as i32);
+		let attributes = if has_vertex_shader { query_attributes(program) } else { Vec::new() };
+
+		let decls: Vec<ImageDecl> = shaders.iter().flat_map(|&s| parse_image_declarations(&glGetShaderSource(s))).collect();
+		let images = uniforms
+			.iter()
+			.filter(|u| is_image_type(u.typ))
+			.map(|u| {
+				let decl = decls.iter().find(|d| d.name == base_name(&u.name));
+				ImageInfo {
+					name: u.name.clone(),
+					unit: match &u.value {
+						UniformValue::Int(v) => v[0] as u32,
+						_ => unreachable!(),
+					},
+					typ: u.typ,
+					format: decl.and_then(|d| d.format),
+					access: decl.map(|d| d.access).unwrap_or(gl::READ_WRITE),
+				}
+			})
+			.collect();
+
+		Self {
+			uniforms,
+			uniform_blocks: query_blocks(program, gl::UNIFORM_BLOCK),
+			storage_blocks: query_blocks(program, gl::SHADER_STORAGE_BLOCK),
+			attributes,
+			images,
+			work_group_size,
+		}
+	}
+
+	/// The active uniform called `name` (array names may omit the `[0]` suffix).
+	pub fn uniform(&self, name: &str) -> Option<&UniformInfo> {
+		self.uniforms.iter().find(|u| u.name == name || base_name(&u.name) == name)
+	}
+
+	/// Check that uniform `name` is active and has GL type `typ` (e.g. FLOAT_VEC2).
+	pub fn check_uniform(&self, name: &str, typ: GLenum) -> Result<&UniformInfo, String> {
+		match self.uniform(name) {
+			None => Err(format!("uniform `{}` not active in program (not declared or optimized out)", name)),
+			Some(u) if u.typ != typ => Err(format!("uniform `{}` has type {}, not {}", name, glsl_type_name(u.typ), glsl_type_name(typ))),
+			Some(u) => Ok(u),
+		}
+	}
+
+	/// The image uniform bound to image unit `unit`.
+	pub fn image(&self, unit: u32) -> Option<&ImageInfo> {
+		self.images.iter().find(|i| i.unit == unit)
+	}
+
+	pub fn uniform_block(&self, name: &str) -> Option<&BlockInfo> {
+		self.uniform_blocks.iter().find(|b| b.name == name)
+	}
+
+	pub fn storage_block(&self, name: &str) -> Option<&BlockInfo> {
+		self.storage_blocks.iter().find(|b| b.name == name)
+	}
+
+	/// Check that storage block `name` is active and laid out like `T` (see `BlockInfo::check_layout`).
+	pub fn check_storage_block<T: Std430>(&self, name: &str) -> Result<&BlockInfo, String> {
+		let block = self.storage_block(name).ok_or_else(|| format!("storage block `{}` not active in program", name))?;
+		block.check_layout::<T>(<T as Std430>::locate, <T as Std430>::STRIDE)?;
+		Ok(block)
+	}
+
+	/// Check that uniform block `name` is active and laid out like `T` (see `BlockInfo::check_layout`).
+	pub fn check_uniform_block<T: Std140>(&self, name: &str) -> Result<&BlockInfo, String> {
+		let block = self.uniform_block(name).ok_or_else(|| format!("uniform block `{}` not active in program", name))?;
+		block.check_layout::<T>(<T as Std140>::locate, <T as Std140>::STRIDE)?;
+		Ok(block)
+	}
+
+	/// Check that every image unit used by the program currently has a texture bound
+	/// with the declared format and a compatible access mode.
+	/// The error names the offending image variable.
+	pub fn check_image_bindings(&self) -> Result<(), String> {
+		for img in &self.images {
+			let texture = glGetIntegeri_v(gl::IMAGE_BINDING_NAME, img.unit);
+			let format = glGetIntegeri_v(gl::IMAGE_BINDING_FORMAT, img.unit) as GLenum;
+			let access = glGetIntegeri_v(gl::IMAGE_BINDING_ACCESS, img.unit) as GLenum;
+			let what = || format!("image `{}` (unit {}, {} {})", img.name, img.unit, access_name(img.access), glsl_type_name(img.typ));
+
+			if texture == 0 {
+				return Err(format!("{}: no texture bound", what()));
+			}
+			registry::check_live("Texture", texture as GLuint, "check_image_bindings");
+			if let Some(want) = img.format {
+				if want != format {
+					return Err(format!(
+						"{}: declared {}, but texture {} is bound as {}",
+						what(),
+						image_format_name(want),
+						texture,
+						image_format_name(format)
+					));
+				}
+			}
+			let class = format_class(format);
+			if class != image_class(img.typ) {
+				return Err(format!("{}: bound texture {} has {} format {}", what(), texture, class, image_format_name(format)));
+			}
+			let compatible = match img.access {
+				gl::READ_ONLY => access == gl::READ_ONLY || access == gl::READ_WRITE,
+				gl::WRITE_ONLY => access == gl::WRITE_ONLY || access == gl::READ_WRITE,
+				_ => access == gl::READ_WRITE,
+			};
+			if !compatible {
+				return Err(format!("{}: texture {} is bound {}", what(), texture, access_name(access)));
+			}
+		}
+		Ok(())
+	}
+}
+
+thread_local! {
+	static CACHE: RefCell<HashMap<GLuint, Rc<ProgramInfo>>> = RefCell::new(HashMap::new());
+}
+
+/// `ProgramInfo::new`, cached until the program is re-linked (see `forget_info`).
+pub(crate) fn cached_info(program: GLuint) -> Rc<ProgramInfo> {
+	CACHE.with(|c| c.borrow_mut().entry(program).or_insert_with(|| Rc::new(ProgramInfo::new(program))).clone())
+}
+
+/// Invalidate the cached `ProgramInfo`, e.g. after (re-)linking.
+pub(crate) fn forget_info(program: GLuint) {
+	CACHE.with(|c| c.borrow_mut().remove(&program));
+}
+
+impl fmt::Display for ProgramInfo {
+	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+		if let Some(s) = self.work_group_size {
+			writeln!(f, "work group size: {} x {} x {}", s.0, s.1, s.2)?;
+		}
+		if !self.attributes.is_empty() {
+			writeln!(f, "inputs:")?;
+		}
+		for a in &self.attributes {
+			writeln!(f, "\tlocation {:2}: {} {}", a.location, glsl_type_name(a.typ), a.name)?;
+		}
+		if !self.uniforms.is_empty() {
+			writeln!(f, "uniforms:")?;
+		}
+		for u in &self.uniforms {
+			let typ = glsl_type_name(u.typ);
+			if let Some(img) = self.images.iter().find(|i| i.name == u.name) {
+				let format = img.format.map(image_format_name).unwrap_or("no format");
+				writeln!(f, "\tlocation {:2}: {} {} (unit {}, {}, {})", u.location, typ, u.name, img.unit, format, access_name(img.access))?;
+			} else if u.array_size > 1 {
+				writeln!(f, "\tlocation {:2}: {} {}[{}] = {}", u.location, typ, base_name(&u.name), u.array_size, u.value)?;
+			} else {
+				writeln!(f, "\tlocation {:2}: {} {} = {}", u.location, typ, u.name, u.value)?;
+			}
+		}
+		for (title, blocks) in &[("uniform blocks", &self.uniform_blocks), ("shader storage blocks", &self.storage_blocks)] {
+			if !blocks.is_empty() {
+				writeln!(f, "{}:", title)?;
+			}
+			for b in blocks.iter() {
+				writeln!(f, "\tbinding {:2}: {} ({} bytes)", b.binding, b.name, b.data_size)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for UniformValue {
+	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+		fn list<T: fmt::Display>(f: &mut fmt::Formatter, v: &[T]) -> Result<(), fmt::Error> {
+			if v.len() == 1 {
+				return write!(f, "{}", v[0]);
+			}
+			let v: Vec<String> = v.iter().map(|x| x.to_string()).collect();
+			write!(f, "({})", v.join(", "))
+		}
+		match self {
+			UniformValue::Float(v) => list(f, v),
+			UniformValue::Double(v) => list(f, v),
+			UniformValue::Int(v) => list(f, v),
+			UniformValue::Uint(v) => list(f, v),
+		}
+	}
+}
+
+fn query_uniforms(program: GLuint) -> Vec<UniformInfo> {
+	let n = glGetProgramInterfaceiv(program, gl::UNIFORM, gl::ACTIVE_RESOURCES);
+	let mut uniforms = Vec::new();
+	for i in 0..(n as u32) {
+		let p = glGetProgramResourceiv(program, gl::UNIFORM, i, &[gl::TYPE, gl::ARRAY_SIZE, gl::LOCATION, gl::BLOCK_INDEX]);
+		let (typ, array_size, location, block_index) = (p[0] as GLenum, p[1], p[2], p[3]);
+		if block_index != -1 {
+			continue; // member of a uniform block, has no location.
+		}
+		// Array elements have consecutive locations, but glGetUniform only returns one element.
+		let locations = (0..array_size.max(1)).map(|e| location + e);
+		let per = type_components(typ);
+		let value = match base_type(typ) {
+			_ if location < 0 => UniformValue::Int(vec![]), // e.g. atomic counters
+			gl::FLOAT => UniformValue::Float(locations.flat_map(|l| glGetnUniformfv(program, l, per)).collect()),
+			gl::DOUBLE => UniformValue::Double(locations.flat_map(|l| glGetnUniformdv(program, l, per)).collect()),
+			gl::UNSIGNED_INT => UniformValue::Uint(locations.flat_map(|l| glGetnUniformuiv(program, l, per)).collect()),
+			_ => UniformValue::Int(locations.flat_map(|l| glGetnUniformiv(program, l, per)).collect()),
+		};
+		uniforms.push(UniformInfo {
+			name: glGetProgramResourceName(program, gl::UNIFORM, i),
+			typ,
+			array_size,
+			location,
+			value,
+		});
+	}
+	uniforms.sort_by_key(|u| u.location);
+	uniforms
+}
+
+fn query_attributes(program: GLuint) -> Vec<AttribInfo> {
+	let n = glGetProgramInterfaceiv(program, gl::PROGRAM_INPUT, gl::ACTIVE_RESOURCES);
+	let mut attributes = Vec::new();
+	for i in 0..(n as u32) {
+		let p = glGetProgramResourceiv(program, gl::PROGRAM_INPUT, i, &[gl::TYPE, gl::LOCATION]);
+		if p[1] < 0 {
+			continue; // built-in, e.g. gl_VertexID
+		}
+		attributes.push(AttribInfo {
+			name: glGetProgramResourceName(program, gl::PROGRAM_INPUT, i),
+			typ: p[0] as GLenum,
+			location: p[1] as u32,
+		});
+	}
+	attributes.sort_by_key(|a| a.location);
+	attributes
+}
+
+fn query_blocks(program: GLuint, interface: GLenum) -> Vec<BlockInfo> {
+	let n = glGetProgramInterfaceiv(program, interface, gl::ACTIVE_RESOURCES);
+	let members = query_block_members(program, interface);
+	let mut blocks: Vec<BlockInfo> = (0..(n as u32))
+		.map(|i| {
+			let p = glGetProgramResourceiv(program, interface, i, &[gl::BUFFER_BINDING, gl::BUFFER_DATA_SIZE]);
+			BlockInfo {
+				name: glGetProgramResourceName(program, interface, i),
+				index: i,
+				binding: p[0] as u32,
+				data_size: p[1] as u32,
+				members: members.iter().filter(|(b, _)| *b == i).map(|(_, m)| m.clone()).collect(),
+			}
+		})
+		.collect();
+	blocks.sort_by_key(|b| b.binding);
+	blocks
+}
+
+// Variables of all blocks of `interface` (UNIFORM_BLOCK or SHADER_STORAGE_BLOCK), with their block index.
+fn query_block_members(program: GLuint, interface: GLenum) -> Vec<(u32, BlockMember)> {
+	let (variables, props): (GLenum, &[GLenum]) = match interface {
+		gl::SHADER_STORAGE_BLOCK => (
+			gl::BUFFER_VARIABLE,
+			&[gl::BLOCK_INDEX, gl::TYPE, gl::OFFSET, gl::ARRAY_STRIDE, gl::TOP_LEVEL_ARRAY_SIZE, gl::TOP_LEVEL_ARRAY_STRIDE],
+		),
+		_ => (gl::UNIFORM, &[gl::BLOCK_INDEX, gl::TYPE, gl::OFFSET, gl::ARRAY_STRIDE]),
+	};
+	let n = glGetProgramInterfaceiv(program, variables, gl::ACTIVE_RESOURCES);
+	let mut members = Vec::new();
+	for i in 0..(n as u32) {
+		let p = glGetProgramResourceiv(program, variables, i, props);
+		if p[0] == -1 {
+			continue; // uniform in the default block
+		}
+		let top_level_array = match p.get(5) {
+			Some(&stride) if stride != 0 => Some((p[4] as u32, stride as u32)),
+			_ => None,
+		};
+		let member = BlockMember {
+			name: glGetProgramResourceName(program, variables, i),
+			typ: p[1] as GLenum,
+			offset: p[2] as u32,
+			array_stride: p[3] as u32,
+			top_level_array,
+		};
+		members.push((p[0] as u32, member));
+	}
+	members.sort_by_key(|(_, m)| m.offset);
+	members
+}
+
+impl BlockInfo {
+	/// Check that the members of the block have the offsets and types of the corresponding fields of `T`,
+	/// as given by `locate` and `stride` (from `Std430` or `Std140`).
+	///
+	/// The block's members must be `T`'s fields, e.g. `buffer Params { float g; vec2 center; };`,
+	/// or the block must hold a single runtime-sized array of `T`, e.g. `buffer Attractors { Attractor attractors[]; };`.
+	pub(crate) fn check_layout<T>(&self, locate: fn(&str) -> Option<(usize, GLenum)>, stride: usize) -> Result<(), String> {
+		let rust_type = std::any::type_name::<T>();
+		for m in &self.members {
+			// "Block.member" if the block has an instance name.
+			let name = m.name.strip_prefix(&format!("{}.", self.name)).unwrap_or(&m.name);
+			let path = match m.top_level_array {
+				Some((0, top_stride)) => {
+					if top_stride as usize != stride {
+						return Err(format!(
+							"block `{}`: array `{}` has a stride of {} bytes in GLSL, but {} has {}",
+							self.name, name, top_stride, rust_type, stride
+						));
+					}
+					let (_, rest) = std_split_field(name);
+					rest.strip_prefix("[0]").map(|r| r.strip_prefix('.').unwrap_or(r)).unwrap_or(rest)
+				}
+				_ => name,
+			};
+			let mismatch = |what: String| format!("block `{}`: member `{}` {} in GLSL, but {}", self.name, name, what, rust_type);
+			let (offset, typ) = locate(path).ok_or_else(|| mismatch("exists".into()) + " has no such field")?;
+			if offset != m.offset as usize || typ != m.typ {
+				return Err(mismatch(format!("is a {} at offset {}", glsl_type_name(m.typ), m.offset)) + &format!(" has a {} at offset {}", glsl_type_name(typ), offset));
+			}
+			// For arrays, also check the offset of the second element.
+			if m.array_stride != 0 && path.ends_with("[0]") {
+				let second = format!("{}[1]", &path[..path.len() - 3]);
+				if let Some((next, _)) = locate(&second) {
+					if next - offset != m.array_stride as usize {
+						return Err(mismatch(format!("has an array stride of {} bytes", m.array_stride)) + &format!(" has {}", next - offset));
+					}
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+// "foo[0]" -> "foo"
+fn base_name(name: &str) -> &str {
+	name.split('[').next().unwrap()
+}
+
+// An image uniform declaration, as found in shader source.
+#[derive(Debug)]
+struct ImageDecl {
+	name: String,
+	format: Option<GLenum>,
+	access: GLenum,
+}
+
+// Find declarations like `layout(binding = 0, rg32f) uniform readonly image2D pos;`.
+// The format and memory qualifiers of images cannot be queried from GL.
+fn parse_image_declarations(src: &str) -> Vec<ImageDecl> {
+	let src = strip_comments(src);
+	let src: String = src.lines().filter(|l| !l.trim_start().starts_with('#')).collect::<Vec<_>>().join("\n");
+
+	let mut decls = Vec::new();
+	for stmt in src.split(&[';', '{', '}'][..]) {
+		let (layout, rest) = match stmt.find("layout") {
+			None => ("", stmt),
+			Some(i) => {
+				let open = match stmt[i..].find('(') {
+					Some(o) => i + o,
+					None => continue,
+				};
+				let close = match stmt[open..].find(')') {
+					Some(c) => open + c,
+					None => continue,
+				};
+				(&stmt[open + 1..close], &stmt[close + 1..])
+			}
+		};
+		let tokens: Vec<&str> = rest.split_whitespace().collect();
+		let uniform = match tokens.iter().position(|&t| t == "uniform") {
+			Some(i) => i,
+			None => continue,
+		};
+		// the type is the first token after `uniform` that is not a qualifier
+		let typ = match (uniform + 1..tokens.len()).find(|&i| !QUALIFIERS.contains(&tokens[i])) {
+			Some(i) => i,
+			None => continue,
+		};
+		let t = tokens[typ];
+		if !(t.starts_with("image") || t.starts_with("iimage") || t.starts_with("uimage")) {
+			continue;
+		}
+		let name = match tokens.get(typ + 1) {
+			Some(n) => base_name(n).to_string(),
+			None => continue,
+		};
+		let readonly = tokens.contains(&"readonly");
+		let writeonly = tokens.contains(&"writeonly");
+		decls.push(ImageDecl {
+			name,
+			format: layout.split(',').filter_map(|q| image_format_from_glsl(q.trim())).next(),
+			access: match (readonly, writeonly) {
+				(true, false) => gl::READ_ONLY,
+				(false, true) => gl::WRITE_ONLY,
+				_ => gl::READ_WRITE,
+			},
+		});
+	}
+	decls
+}
+
+// Qualifiers that may appear between `uniform` and the type of an image.
+const QUALIFIERS: &[&str] = &["readonly", "writeonly", "coherent", "volatile", "restrict", "highp", "mediump", "lowp"];
+
+fn strip_comments(src: &str) -> String {
+	let mut out = String::with_capacity(src.len());
+	let mut rest = src;
+	while !rest.is_empty() {
+		if rest.starts_with("//") {
+			rest = &rest[rest.find('\n').unwrap_or(rest.len())..];
+		} else if rest.starts_with("/*") {
+			let end = rest[2..].find("*/").map(|i| i + 4).unwrap_or(rest.len());
+			// keep newlines, so that line numbers stay the same.
+			out.extend(rest[..end].chars().filter(|&c| c == '\n'));
+			rest = &rest[end..];
+		} else {
+			let c = rest.chars().next().unwrap();
+			out.push(c);
+			rest = &rest[c.len_utf8()..];
+		}
+	}
+	out
+}
+
+const IMAGE_FORMATS: &[(&str, GLenum)] = &[
+	("rgba32f", gl::RGBA32F),
+	("rgba16f", gl::RGBA16F),
+	("rg32f", gl::RG32F),
+	("rg16f", gl::RG16F),
+	("r11f_g11f_b10f", gl::R11F_G11F_B10F),
+	("r32f", gl::R32F),
+	("r16f", gl::R16F),
+	("rgba16", gl::RGBA16),
+	("rgb10_a2", gl::RGB10_A2),
+	("rgba8", gl::RGBA8),
+	("rg16", gl::RG16),
+	("rg8", gl::RG8),
+	("r16", gl::R16),
+	("r8", gl::R8),
+	("rgba16_snorm", gl::RGBA16_SNORM),
+	("rgba8_snorm", gl::RGBA8_SNORM),
+	("rg16_snorm", gl::RG16_SNORM),
+	("rg8_snorm", gl::RG8_SNORM),
+	("r16_snorm", gl::R16_SNORM),
+	("r8_snorm", gl::R8_SNORM),
+	("rgba32i", gl::RGBA32I),
+	("rgba16i", gl::RGBA16I),
+	("rgba8i", gl::RGBA8I),
+	("rg32i", gl::RG32I),
+	("rg16i", gl::RG16I),
+	("rg8i", gl::RG8I),
+	("r32i", gl::R32I),
+	("r16i", gl::R16I),
+	("r8i", gl::R8I),
+	("rgba32ui", gl::RGBA32UI),
+	("rgba16ui", gl::RGBA16UI),
+	("rgb10_a2ui", gl::RGB10_A2UI),
+	("rgba8ui", gl::RGBA8UI),
+	("rg32ui", gl::RG32UI),
+	("rg16ui", gl::RG16UI),
+	("rg8ui", gl::RG8UI),
+	("r32ui", gl::R32UI),
+	("r16ui", gl::R16UI),
+	("r8ui", gl::R8UI),
+];
+
+/// Image format for a GLSL format layout qualifier, e.g. "rg32f" -> RG32F.
+pub fn image_format_from_glsl(qualifier: &str) -> Option<GLenum> {
+	IMAGE_FORMATS.iter().find(|(name, _)| *name == qualifier).map(|(_, f)| *f)
+}
+
+/// GLSL format layout qualifier for an image format, e.g. RG32F -> "rg32f".
+pub fn image_format_name(format: GLenum) -> &'static str {
+	IMAGE_FORMATS.iter().find(|(_, f)| *f == format).map(|(name, _)| *name).unwrap_or("unknown format")
+}
+
+/// "readonly", "writeonly" or "read-write".
+pub fn access_name(access: GLenum) -> &'static str {
+	match access {
+		gl::READ_ONLY => "readonly",
+		gl::WRITE_ONLY => "writeonly",
+		gl::READ_WRITE => "read-write",
+		_ => "unknown access",
+	}
+}
+
+// (type, GLSL name, components, base type)
+const TYPES: &[(GLenum, &str, usize, GLenum)] = &[
+	(gl::FLOAT, "float", 1, gl::FLOAT),
+	(gl::FLOAT_VEC2, "vec2", 2, gl::FLOAT),
+	(gl::FLOAT_VEC3, "vec3", 3, gl::FLOAT),
+	(gl::FLOAT_VEC4, "vec4", 4, gl::FLOAT),
+	(gl::DOUBLE, "double", 1, gl::DOUBLE),
+	(gl::DOUBLE_VEC2, "dvec2", 2, gl::DOUBLE),
+	(gl::DOUBLE_VEC3, "dvec3", 3, gl::DOUBLE),
+	(gl::DOUBLE_VEC4, "dvec4", 4, gl::DOUBLE),
+	(gl::INT, "int", 1, gl::INT),
+	(gl::INT_VEC2, "ivec2", 2, gl::INT),
+	(gl::INT_VEC3, "ivec3", 3, gl::INT),
+	(gl::INT_VEC4, "ivec4", 4, gl::INT),
+	(gl::UNSIGNED_INT, "uint", 1, gl::UNSIGNED_INT),
+	(gl::UNSIGNED_INT_VEC2, "uvec2", 2, gl::UNSIGNED_INT),
+	(gl::UNSIGNED_INT_VEC3, "uvec3", 3, gl::UNSIGNED_INT),
+	(gl::UNSIGNED_INT_VEC4, "uvec4", 4, gl::UNSIGNED_INT),
+	(gl::BOOL, "bool", 1, gl::BOOL),
+	(gl::BOOL_VEC2, "bvec2", 2, gl::BOOL),
+	(gl::BOOL_VEC3, "bvec3", 3, gl::BOOL),
+	(gl::BOOL_VEC4, "bvec4", 4, gl::BOOL),
+	(gl::FLOAT_MAT2, "mat2", 4, gl::FLOAT),
+	(gl::FLOAT_MAT3, "mat3", 9, gl::FLOAT),
+	(gl::FLOAT_MAT4, "mat4", 16, gl::FLOAT),
+	(gl::FLOAT_MAT2x3, "mat2x3", 6, gl::FLOAT),
+	(gl::FLOAT_MAT2x4, "mat2x4", 8, gl::FLOAT),
+	(gl::FLOAT_MAT3x2, "mat3x2", 6, gl::FLOAT),
+	(gl::FLOAT_MAT3x4, "mat3x4", 12, gl::FLOAT),
+	(gl::FLOAT_MAT4x2, "mat4x2", 8, gl::FLOAT),
+	(gl::FLOAT_MAT4x3, "mat4x3", 12, gl::FLOAT),
+	(gl::DOUBLE_MAT2, "dmat2", 4, gl::DOUBLE),
+	(gl::DOUBLE_MAT3, "dmat3", 9, gl::DOUBLE),
+	(gl::DOUBLE_MAT4, "dmat4", 16, gl::DOUBLE),
+	(gl::SAMPLER_1D, "sampler1D", 1, gl::INT),
+	(gl::SAMPLER_2D, "sampler2D", 1, gl::INT),
+	(gl::SAMPLER_3D, "sampler3D", 1, gl::INT),
+	(gl::SAMPLER_CUBE, "samplerCube", 1, gl::INT),
+	(gl::SAMPLER_2D_ARRAY, "sampler2DArray", 1, gl::INT),
+	(gl::SAMPLER_2D_SHADOW, "sampler2DShadow", 1, gl::INT),
+	(gl::INT_SAMPLER_1D, "isampler1D", 1, gl::INT),
+	(gl::INT_SAMPLER_2D, "isampler2D", 1, gl::INT),
+	(gl::INT_SAMPLER_3D, "isampler3D", 1, gl::INT),
+	(gl::UNSIGNED_INT_SAMPLER_1D, "usampler1D", 1, gl::INT),
+	(gl::UNSIGNED_INT_SAMPLER_2D, "usampler2D", 1, gl::INT),
+	(gl::UNSIGNED_INT_SAMPLER_3D, "usampler3D", 1, gl::INT),
+	(gl::IMAGE_1D, "image1D", 1, gl::INT),
+	(gl::IMAGE_2D, "image2D", 1, gl::INT),
+	(gl::IMAGE_3D, "image3D", 1, gl::INT),
+	(gl::IMAGE_2D_ARRAY, "image2DArray", 1, gl::INT),
+	(gl::IMAGE_CUBE, "imageCube", 1, gl::INT),
+	(gl::INT_IMAGE_1D, "iimage1D", 1, gl::INT),
+	(gl::INT_IMAGE_2D, "iimage2D", 1, gl::INT),
+	(gl::INT_IMAGE_3D, "iimage3D", 1, gl::INT),
+	(gl::INT_IMAGE_2D_ARRAY, "iimage2DArray", 1, gl::INT),
+	(gl::UNSIGNED_INT_IMAGE_1D, "uimage1D", 1, gl::INT),
+	(gl::UNSIGNED_INT_IMAGE_2D, "uimage2D", 1, gl::INT),
+	(gl::UNSIGNED_INT_IMAGE_3D, "uimage3D", 1, gl::INT),
+	(gl::UNSIGNED_INT_IMAGE_2D_ARRAY, "uimage2DArray", 1, gl::INT),
+	(gl::UNSIGNED_INT_ATOMIC_COUNTER, "atomic_uint", 1, gl::UNSIGNED_INT),
+];
+
+/// GLSL name of a uniform type, e.g. FLOAT_VEC2 -> "vec2".
+pub fn glsl_type_name(typ: GLenum) -> &'static str {
+	TYPES.iter().find(|t| t.0 == typ).map(|t| t.1).unwrap_or("unknown type")
+}
+
+// Number of scalar components, e.g. FLOAT_VEC2 -> 2.
+fn type_components(typ: GLenum) -> usize {
+	TYPES.iter().find(|t| t.0 == typ).map(|t| t.2).unwrap_or(1)
+}
+
+// Scalar type, e.g. FLOAT_VEC2 -> FLOAT. Samplers and images are INT.
+fn base_type(typ: GLenum) -> GLenum {
+	TYPES.iter().find(|t| t.0 == typ).map(|t| t.3).unwrap_or(gl::INT)
+}
+
+fn is_image_type(typ: GLenum) -> bool {
+	glsl_type_name(typ).contains("image")
+}
+
+// "float", "int" or "uint": the kind of data an image type (e.g. uimage2D) reads and writes.
+fn image_class(typ: GLenum) -> &'static str {
+	match glsl_type_name(typ).chars().next() {
+		Some('u') => "uint",
+		Some('i') if glsl_type_name(typ).starts_with("iimage") => "int",
+		_ => "float",
+	}
+}
+
+// "float", "int" or "uint": the kind of image format (e.g. RGBA8UI) that goes with it.
+fn format_class(format: GLenum) -> &'static str {
+	let name = image_format_name(format);
+	if name.ends_with("ui") {
+		"uint"
+	} else if name.ends_with('i') {
+		"int"
+	} else {
+		"float"
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn decls(src: &str) -> Vec<(String, Option<GLenum>, GLenum)> {
+		parse_image_declarations(src).into_iter().map(|d| (d.name, d.format, d.access)).collect()
+	}
+
+	#[test]
+	fn image_access() {
+		let src = "
+			#version 450
+			layout(local_size_x = 16, local_size_y = 16) in;
+			layout(binding = 0, rg32f) uniform readonly image2D pos;
+			layout(r32ui, binding = 1) uniform writeonly uimage2D density;
+			layout(binding = 2) uniform image2D color;
+		";
+		assert_eq!(
+			decls(src),
+			vec![
+				("pos".to_string(), Some(gl::RG32F), gl::READ_ONLY),
+				("density".to_string(), Some(gl::R32UI), gl::WRITE_ONLY),
+				("color".to_string(), None, gl::READ_WRITE),
+			]
+		);
+	}
+
+	#[test]
+	fn image_commented_out() {
+		let src = "
+			// layout(binding = 0, rg32f) uniform readonly image2D old;
+			/* layout(binding = 1, rg32f) uniform
+			   writeonly image2D older; */
+			layout(binding = 2, rgba8) uniform coherent writeonly image2D current;
+		";
+		assert_eq!(decls(src), vec![("current".to_string(), Some(gl::RGBA8), gl::WRITE_ONLY)]);
+	}
+
+	#[test]
+	fn image_array() {
+		let src = "layout(binding = 0, r32f) uniform readonly image2D img[2];";
+		assert_eq!(decls(src), vec![("img".to_string(), Some(gl::R32F), gl::READ_ONLY)]);
+	}
+
+	#[test]
+	fn non_image_uniforms() {
+		let src = "
+			uniform float scale;
+			layout(binding = 3) uniform usampler2D image_tex;
+			layout(std430, binding = 0) buffer Particles { vec2 pos[]; };
+			layout(std140, binding = 1) uniform Params { float dt; };
+		";
+		assert!(decls(src).is_empty());
+	}
+
+	#[test]
+	fn strip_comments_keeps_lines() {
+		let src = "a // one\n/* two\nthree */ b\nc";
+		assert_eq!(strip_comments(src), "a \n\n b\nc");
+	}
+}
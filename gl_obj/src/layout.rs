@@ -0,0 +1,451 @@
+use super::*;
+use gl_safe::*;
+use std::convert::TryInto;
+
+/// A Rust type that can be stored in a GLSL shader storage block (or uniform block) with `layout(std430)`.
+///
+/// The Rust memory layout is irrelevant: values are written to (and read from) bytes at the offsets
+/// dictated by the std430 rules, which are computed from each type's alignment and size.
+/// Implemented for `f32`, `i32`, `u32`, the `gl_vec` vector and matrix types and arrays of those,
+/// and for structs with `#[derive(Std430)]`:
+///
+/// ```ignore
+/// #[derive(Clone, Copy, Std430)]
+/// struct Attractor {
+///     pos: vec2,
+///     mass: f32,
+/// }
+///
+/// // "struct Attractor {\n\tvec2 pos;\n\tfloat mass;\n};\n", to be pasted into (or injected in) the shader.
+/// let glsl = Attractor::glsl_declarations();
+/// ```
+///
+/// `Std430` and `Std140` have the same items, use qualified paths (`<T as Std430>::SIZE`) when both are implemented.
+pub trait Std430: Sized + Copy + 'static {
+	/// Base alignment in bytes.
+	const ALIGN: usize;
+
+	/// Size in bytes, without padding to the array stride.
+	const SIZE: usize;
+
+	/// Distance between consecutive array elements, in bytes.
+	const STRIDE: usize = std_align_up(Self::SIZE, Self::ALIGN);
+
+	/// GLSL type, e.g. "vec2", "float[4]" or a struct name.
+	fn glsl_type() -> String;
+
+	/// Add the GLSL struct declarations this type needs (its own and those of nested structs) to `decls`,
+	/// skipping those that are already there.
+	fn declare(_decls: &mut Vec<String>) {}
+
+	/// GLSL declarations of this type and all the struct types it depends on, in order.
+	fn glsl_declarations() -> String {
+		let mut decls = Vec::new();
+		Self::declare(&mut decls);
+		decls.concat()
+	}
+
+	/// Byte offset and GL type (e.g. FLOAT_VEC2) of a member, given its path as reported by
+	/// program introspection, relative to this type: e.g. "" (this value), "[2]" (array element), "pos" (struct field).
+	fn locate(path: &str) -> Option<(usize, GLenum)>;
+
+	/// Write the value to the start of `out`, which is at least `SIZE` bytes long. Padding is left alone.
+	fn write(&self, out: &mut [u8]);
+
+	/// Read a value from the start of `bytes`.
+	fn read(bytes: &[u8]) -> Self;
+}
+
+/// Like `Std430`, but with the rules of `layout(std140)`, the default for uniform blocks:
+/// array elements and structs are aligned to 16 bytes.
+/// Implemented for the same types, and for structs with `#[derive(Std140)]`.
+pub trait Std140: Sized + Copy + 'static {
+	/// Base alignment in bytes.
+	const ALIGN: usize;
+
+	/// Size in bytes, without padding to the array stride.
+	const SIZE: usize;
+
+	/// Distance between consecutive array elements, in bytes.
+	const STRIDE: usize = std_align_up(Self::SIZE, std_max(Self::ALIGN, 16));
+
+	/// GLSL type, e.g. "vec2", "float[4]" or a struct name.
+	fn glsl_type() -> String;
+
+	/// See `Std430::declare`.
+	fn declare(_decls: &mut Vec<String>) {}
+
+	/// See `Std430::glsl_declarations`.
+	fn glsl_declarations() -> String {
+		let mut decls = Vec::new();
+		Self::declare(&mut decls);
+		decls.concat()
+	}
+
+	/// See `Std430::locate`.
+	fn locate(path: &str) -> Option<(usize, GLenum)>;
+
+	/// See `Std430::write`.
+	fn write(&self, out: &mut [u8]);
+
+	/// See `Std430::read`.
+	fn read(bytes: &[u8]) -> Self;
+}
+
+// Scalars and vectors have the same layout under both rules.
+macro_rules! std_vector {
+	($t:ty, $scalar:ty, $gl_type:ident, $glsl:expr, $align:expr, $n:expr, |$v:ident| $to:expr, |$w:ident| $from:expr) => {
+		std_vector!(@impl Std430, $t, $scalar, $gl_type, $glsl, $align, $n, |$v| $to, |$w| $from);
+		std_vector!(@impl Std140, $t, $scalar, $gl_type, $glsl, $align, $n, |$v| $to, |$w| $from);
+	};
+	(@impl $trait:ident, $t:ty, $scalar:ty, $gl_type:ident, $glsl:expr, $align:expr, $n:expr, |$v:ident| $to:expr, |$w:ident| $from:expr) => {
+		impl $trait for $t {
+			const ALIGN: usize = $align;
+			const SIZE: usize = 4 * $n;
+
+			fn glsl_type() -> String {
+				$glsl.into()
+			}
+
+			fn locate(path: &str) -> Option<(usize, GLenum)> {
+				if path.is_empty() {
+					Some((0, gl::$gl_type))
+				} else {
+					None
+				}
+			}
+
+			fn write(&self, out: &mut [u8]) {
+				let $v = self;
+				let words: [$scalar; $n] = $to;
+				for (i, w) in words.iter().enumerate() {
+					out[4 * i..4 * i + 4].copy_from_slice(&w.to_ne_bytes());
+				}
+			}
+
+			fn read(bytes: &[u8]) -> Self {
+				let mut $w = [<$scalar>::default(); $n];
+				for (i, w) in $w.iter_mut().enumerate() {
+					*w = <$scalar>::from_ne_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap());
+				}
+				$from
+			}
+		}
+	};
+}
+
+std_vector!(f32, f32, FLOAT, "float", 4, 1, |v| [*v], |w| w[0]);
+std_vector!(vec2, f32, FLOAT_VEC2, "vec2", 8, 2, |v| [v.0, v.1], |w| vec2(w[0], w[1]));
+std_vector!(vec3, f32, FLOAT_VEC3, "vec3", 16, 3, |v| [v.0, v.1, v.2], |w| vec3(w[0], w[1], w[2]));
+std_vector!(vec4, f32, FLOAT_VEC4, "vec4", 16, 4, |v| [v.0, v.1, v.2, v.3], |w| vec4(w[0], w[1], w[2], w[3]));
+std_vector!(i32, i32, INT, "int", 4, 1, |v| [*v], |w| w[0]);
+std_vector!(ivec2, i32, INT_VEC2, "ivec2", 8, 2, |v| [v.0, v.1], |w| ivec2(w[0], w[1]));
+std_vector!(ivec3, i32, INT_VEC3, "ivec3", 16, 3, |v| [v.0, v.1, v.2], |w| ivec3(w[0], w[1], w[2]));
+std_vector!(ivec4, i32, INT_VEC4, "ivec4", 16, 4, |v| [v.0, v.1, v.2, v.3], |w| ivec4(w[0], w[1], w[2], w[3]));
+std_vector!(u32, u32, UNSIGNED_INT, "uint", 4, 1, |v| [*v], |w| w[0]);
+std_vector!(uvec2, u32, UNSIGNED_INT_VEC2, "uvec2", 8, 2, |v| [v.0, v.1], |w| uvec2(w[0], w[1]));
+std_vector!(uvec3, u32, UNSIGNED_INT_VEC3, "uvec3", 16, 3, |v| [v.0, v.1, v.2], |w| uvec3(w[0], w[1], w[2]));
+std_vector!(uvec4, u32, UNSIGNED_INT_VEC4, "uvec4", 16, 4, |v| [v.0, v.1, v.2, v.3], |w| uvec4(w[0], w[1], w[2], w[3]));
+
+// Matrices are stored as arrays of column vectors, so their column stride depends on the rules.
+macro_rules! std_matrix {
+	($trait:ident, $t:ident, $col:ident, $gl_type:ident, $glsl:expr, $stride:expr, $($i:tt),*) => {
+		impl $trait for $t {
+			const ALIGN: usize = $stride;
+			const SIZE: usize = $stride * [$($i),*].len();
+
+			fn glsl_type() -> String {
+				$glsl.into()
+			}
+
+			fn locate(path: &str) -> Option<(usize, GLenum)> {
+				if path.is_empty() {
+					Some((0, gl::$gl_type))
+				} else {
+					None
+				}
+			}
+
+			fn write(&self, out: &mut [u8]) {
+				$(<$col as $trait>::write(&self.$i, &mut out[$i * $stride..]);)*
+			}
+
+			fn read(bytes: &[u8]) -> Self {
+				$t($(<$col as $trait>::read(&bytes[$i * $stride..])),*)
+			}
+		}
+	};
+}
+
+std_matrix!(Std430, mat2, vec2, FLOAT_MAT2, "mat2", 8, 0, 1);
+std_matrix!(Std430, mat3, vec3, FLOAT_MAT3, "mat3", 16, 0, 1, 2);
+std_matrix!(Std430, mat4, vec4, FLOAT_MAT4, "mat4", 16, 0, 1, 2, 3);
+std_matrix!(Std140, mat2, vec2, FLOAT_MAT2, "mat2", 16, 0, 1);
+std_matrix!(Std140, mat3, vec3, FLOAT_MAT3, "mat3", 16, 0, 1, 2);
+std_matrix!(Std140, mat4, vec4, FLOAT_MAT4, "mat4", 16, 0, 1, 2, 3);
+
+macro_rules! std_array {
+	($trait:ident, $min_align:expr) => {
+		impl<T, const N: usize> $trait for [T; N]
+		where
+			T: $trait,
+		{
+			const ALIGN: usize = std_max(T::ALIGN, $min_align);
+			const SIZE: usize = N * T::STRIDE;
+			const STRIDE: usize = Self::SIZE;
+
+			fn glsl_type() -> String {
+				// Arrays of arrays: float[2][3] is an array of 2 float[3]'s.
+				let elem = T::glsl_type();
+				match elem.find('[') {
+					Some(i) => format!("{}[{}]{}", &elem[..i], N, &elem[i..]),
+					None => format!("{}[{}]", elem, N),
+				}
+			}
+
+			fn declare(decls: &mut Vec<String>) {
+				T::declare(decls)
+			}
+
+			fn locate(path: &str) -> Option<(usize, GLenum)> {
+				let (index, rest) = std_split_index(path)?;
+				if index >= N {
+					return None;
+				}
+				let (offset, typ) = T::locate(rest)?;
+				Some((index * T::STRIDE + offset, typ))
+			}
+
+			fn write(&self, out: &mut [u8]) {
+				for (i, v) in self.iter().enumerate() {
+					v.write(&mut out[i * T::STRIDE..]);
+				}
+			}
+
+			fn read(bytes: &[u8]) -> Self {
+				let v: Vec<T> = (0..N).map(|i| T::read(&bytes[i * T::STRIDE..])).collect();
+				match v.try_into() {
+					Ok(array) => array,
+					Err(_) => unreachable!(),
+				}
+			}
+		}
+	};
+}
+
+std_array!(Std430, 1);
+std_array!(Std140, 16);
+
+// Helpers for the code generated by #[derive(Std430)] and #[derive(Std140)].
+// A struct's fields are described by their (alignment, size).
+
+#[doc(hidden)]
+pub const fn std_max(a: usize, b: usize) -> usize {
+	if a > b {
+		a
+	} else {
+		b
+	}
+}
+
+#[doc(hidden)]
+pub const fn std_align_up(offset: usize, align: usize) -> usize {
+	offset.div_ceil(align) * align
+}
+
+#[doc(hidden)]
+pub const fn std_struct_align(fields: &[(usize, usize)], min_align: usize) -> usize {
+	let mut align = min_align;
+	let mut i = 0;
+	while i < fields.len() {
+		align = std_max(align, fields[i].0);
+		i += 1;
+	}
+	align
+}
+
+#[doc(hidden)]
+pub const fn std_field_offset(fields: &[(usize, usize)], field: usize) -> usize {
+	let mut offset = 0;
+	let mut i = 0;
+	while i < field {
+		offset = std_align_up(offset, fields[i].0) + fields[i].1;
+		i += 1;
+	}
+	std_align_up(offset, fields[field].0)
+}
+
+#[doc(hidden)]
+pub const fn std_struct_size(fields: &[(usize, usize)], align: usize) -> usize {
+	if fields.is_empty() {
+		return 0;
+	}
+	let last = fields.len() - 1;
+	std_align_up(std_field_offset(fields, last) + fields[last].1, align)
+}
+
+// "float[4]", "x" -> "\tfloat x[4];\n"
+#[doc(hidden)]
+pub fn std_member_decl(typ: &str, name: &str) -> String {
+	match typ.find('[') {
+		Some(i) => format!("\t{} {}{};\n", &typ[..i], name, &typ[i..]),
+		None => format!("\t{} {};\n", typ, name),
+	}
+}
+
+// "pos.x" -> ("pos", "x"), "p[1].x" -> ("p", "[1].x").
+#[doc(hidden)]
+pub fn std_split_field(path: &str) -> (&str, &str) {
+	let end = path.find(['.', '[']).unwrap_or(path.len());
+	let rest = &path[end..];
+	(&path[..end], rest.strip_prefix('.').unwrap_or(rest))
+}
+
+// "[1].x" -> (1, "x"), "[1][2]" -> (1, "[2]").
+fn std_split_index(path: &str) -> Option<(usize, &str)> {
+	let close = path.find(']')?;
+	let index = path.strip_prefix('[')?[..close - 1].parse().ok()?;
+	let rest = &path[close + 1..];
+	Some((index, rest.strip_prefix('.').unwrap_or(rest)))
+}
+
+/// Encode `data` as an array with the std430 layout (e.g. for a shader storage buffer).
+pub fn std430_bytes<T: Std430>(data: &[T]) -> Vec<u8> {
+	let mut bytes = vec![0; data.len() * T::STRIDE];
+	for (i, v) in data.iter().enumerate() {
+		v.write(&mut bytes[i * T::STRIDE..]);
+	}
+	bytes
+}
+
+/// Encode `data` as an array with the std140 layout (e.g. for a uniform buffer).
+pub fn std140_bytes<T: Std140>(data: &[T]) -> Vec<u8> {
+	let mut bytes = vec![0; data.len() * T::STRIDE];
+	for (i, v) in data.iter().enumerate() {
+		v.write(&mut bytes[i * T::STRIDE..]);
+	}
+	bytes
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[derive(Clone, Copy, Std430, Std140)]
+	struct Inner {
+		a: f32,
+		b: vec2,
+	}
+
+	#[derive(Clone, Copy, Std430, Std140)]
+	struct Outer {
+		x: f32,
+		inner: Inner,
+		list: [Inner; 2],
+	}
+
+	#[derive(Clone, Copy, Std430)]
+	struct Vec3Float {
+		v: vec3,
+		f: f32,
+	}
+
+	fn f32_at(bytes: &[u8], offset: usize) -> f32 {
+		f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+	}
+
+	#[test]
+	fn field_offsets() {
+		// {vec3, float}: the float fits in the vec3's padding.
+		let fields = [(16, 12), (4, 4)];
+		assert_eq!(std_field_offset(&fields, 0), 0);
+		assert_eq!(std_field_offset(&fields, 1), 12);
+		assert_eq!(std_struct_size(&fields, std_struct_align(&fields, 1)), 16);
+
+		// {float, vec2}: the vec2 is aligned to 8 bytes.
+		let fields = [(4, 4), (8, 8)];
+		assert_eq!(std_field_offset(&fields, 1), 8);
+		assert_eq!(std_struct_size(&fields, 8), 16);
+		assert_eq!(std_struct_size(&[], 16), 0);
+	}
+
+	#[test]
+	fn std430_vec3_float() {
+		assert_eq!(Vec3Float::locate("v"), Some((0, gl::FLOAT_VEC3)));
+		assert_eq!(Vec3Float::locate("f"), Some((12, gl::FLOAT)));
+		assert_eq!(<Vec3Float as Std430>::ALIGN, 16);
+		assert_eq!(<Vec3Float as Std430>::SIZE, 16);
+		assert_eq!(<Vec3Float as Std430>::STRIDE, 16);
+	}
+
+	#[test]
+	fn arrays() {
+		// std140 rounds the array stride up to 16 bytes, std430 does not.
+		assert_eq!(<[f32; 4] as Std140>::SIZE, 64);
+		assert_eq!(<[f32; 4] as Std140>::locate("[1]"), Some((16, gl::FLOAT)));
+		assert_eq!(<[f32; 4] as Std430>::SIZE, 16);
+		assert_eq!(<[f32; 4] as Std430>::locate("[1]"), Some((4, gl::FLOAT)));
+		assert_eq!(<[f32; 4] as Std430>::locate("[4]"), None);
+		assert_eq!(<[[f32; 3]; 2] as Std430>::glsl_type(), "float[2][3]");
+	}
+
+	#[test]
+	fn std140_mat3() {
+		assert_eq!(<mat3 as Std140>::SIZE, 48);
+		assert_eq!(<mat3 as Std140>::ALIGN, 16);
+		let m = mat3(vec3(1.0, 2.0, 3.0), vec3(4.0, 5.0, 6.0), vec3(7.0, 8.0, 9.0));
+		let bytes = std140_bytes(&[m]);
+		assert_eq!(bytes.len(), 48);
+		assert_eq!(f32_at(&bytes, 16), 4.0); // columns are 16 bytes apart
+		assert_eq!(f32_at(&bytes, 32), 7.0);
+		assert_eq!(f32_at(&bytes, 40), 9.0);
+		let m2 = <mat3 as Std140>::read(&bytes);
+		assert_eq!(m2.2 .2, 9.0);
+	}
+
+	#[test]
+	fn nested_structs() {
+		assert_eq!(
+			<Outer as Std430>::glsl_declarations(),
+			"struct Inner {\n\tfloat a;\n\tvec2 b;\n};\nstruct Outer {\n\tfloat x;\n\tInner inner;\n\tInner list[2];\n};\n"
+		);
+
+		// std430: Inner is aligned to its vec2 (8 bytes).
+		assert_eq!(<Inner as Std430>::SIZE, 16);
+		assert_eq!(<Outer as Std430>::locate("inner.b"), Some((16, gl::FLOAT_VEC2)));
+		assert_eq!(<Outer as Std430>::locate("list[1].b"), Some((48, gl::FLOAT_VEC2)));
+		assert_eq!(<Outer as Std430>::SIZE, 56);
+
+		// std140: structs are aligned to 16 bytes.
+		assert_eq!(<Outer as Std140>::locate("inner.b"), Some((24, gl::FLOAT_VEC2)));
+		assert_eq!(<Outer as Std140>::locate("list[1].b"), Some((56, gl::FLOAT_VEC2)));
+		assert_eq!(<Outer as Std140>::SIZE, 64);
+		assert_eq!(<Outer as Std140>::locate("nope"), None);
+	}
+
+	#[test]
+	fn write_read() {
+		let inner = Inner { a: 1.0, b: vec2(2.0, 3.0) };
+		let outer = Outer {
+			x: 4.0,
+			inner,
+			list: [inner, Inner { a: 5.0, b: vec2(6.0, 7.0) }],
+		};
+		let bytes = std430_bytes(&[outer]);
+		assert_eq!(bytes.len(), 56);
+		assert_eq!(f32_at(&bytes, 52), 7.0);
+		let back = <Outer as Std430>::read(&bytes);
+		assert_eq!(back.x, 4.0);
+		assert_eq!((back.list[1].b.0, back.list[1].b.1), (6.0, 7.0));
+	}
+
+	#[test]
+	fn split_paths() {
+		assert_eq!(std_split_index("[1].x"), Some((1, "x")));
+		assert_eq!(std_split_index("[1][2]"), Some((1, "[2]")));
+		assert_eq!(std_split_index("x"), None);
+		assert_eq!(std_split_field("p[1].x"), ("p", "[1].x"));
+		assert_eq!(std_split_field("pos.x"), ("pos", "x"));
+		assert_eq!(std_split_field("pos"), ("pos", ""));
+	}
+}
@@ -1,20 +1,61 @@
+extern crate gl_derive;
 extern crate gl_safe;
 extern crate gl_vec;
 
+// The derives refer to `::gl_obj`, this makes them work inside this crate too (e.g. in tests).
+extern crate self as gl_obj;
+
+pub use gl_derive::{Std140, Std430, Vertex};
 pub use gl_vec::*;
 
+mod blockbuffer;
 mod buffer;
+mod compute;
 mod consts;
+mod fence;
+mod framebuffer;
+mod hotprogram;
+mod indirect;
+mod introspect;
+mod layout;
+mod pingpong;
+mod pixelformat;
+mod preprocess;
+mod profiler;
 mod program;
+mod query;
+mod readback;
+mod registry;
 mod shader;
+mod shadererror;
 mod texture;
+mod uniform;
 mod util;
+mod vertex;
 mod vertexarray;
 
+pub use blockbuffer::*;
 pub use buffer::*;
+pub use compute::*;
 pub use consts::*;
+pub use fence::*;
+pub use framebuffer::*;
+pub use hotprogram::*;
+pub use indirect::*;
+pub use introspect::*;
+pub use layout::*;
+pub use pingpong::*;
+pub use pixelformat::*;
+pub use preprocess::*;
+pub use profiler::*;
 pub use program::*;
+pub use query::*;
+pub use readback::*;
+pub use registry::report_live_objects;
 pub use shader::*;
+pub use shadererror::*;
 pub use texture::*;
+pub use uniform::*;
 pub use util::*;
+pub use vertex::*;
 pub use vertexarray::*;
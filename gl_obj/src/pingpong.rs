@@ -0,0 +1,136 @@
+use super::*;
+use gl_safe::*;
+
+/// A GL object that a shader can read from or write to at a numbered binding point,
+/// e.g. a texture (bound to an image unit) or a buffer (bound as shader storage block).
+pub trait Bindable {
+	/// Bind for reading, e.g. as image unit with READ_ONLY access.
+	fn bind_read(&self, unit: u32);
+
+	/// Bind for writing, e.g. as image unit with WRITE_ONLY access.
+	fn bind_write(&self, unit: u32);
+}
+
+impl<F> Bindable for Texture<F>
+where
+	F: PixelFormat,
+{
+	fn bind_read(&self, unit: u32) {
+		self.bind_image_unit(unit, gl::READ_ONLY)
+	}
+
+	fn bind_write(&self, unit: u32) {
+		self.bind_image_unit(unit, gl::WRITE_ONLY)
+	}
+}
+
+// Buffer access is determined by the block's qualifiers in GLSL (readonly, writeonly),
+// so reading and writing use the same shader storage binding.
+impl<T> Bindable for Buffer<T>
+where
+	T: Sized + Copy + 'static,
+{
+	fn bind_read(&self, unit: u32) {
+		self.bind_base(gl::SHADER_STORAGE_BUFFER, unit)
+	}
+
+	fn bind_write(&self, unit: u32) {
+		self.bind_base(gl::SHADER_STORAGE_BUFFER, unit)
+	}
+}
+
+/// Two copies of a texture or buffer, for passes that must not read and write the same data.
+/// A pass reads the `front` (the current state) and writes the `back` (the next state),
+/// after which `swap` makes the back the new front.
+///
+/// E.g. a time step:
+///
+/// ```ignore
+/// pos.bind(0, 1); // read pos.front() at image unit 0, write pos.back() at unit 1
+/// program.compute_and_sync(size)?;
+/// pos.swap();
+/// ```
+pub struct PingPong<T> {
+	copies: [T; 2],
+	front: usize,
+}
+
+impl<T> PingPong<T> {
+	/// Double buffer with given initial `front` and `back`.
+	pub fn new(front: T, back: T) -> Self {
+		Self { copies: [front, back], front: 0 }
+	}
+
+	/// The current state, to be read by the next pass.
+	pub fn front(&self) -> &T {
+		&self.copies[self.front]
+	}
+
+	/// The next state, to be written by the next pass.
+	pub fn back(&self) -> &T {
+		&self.copies[1 - self.front]
+	}
+
+	/// Exchange front and back, after a pass has written the back.
+	pub fn swap(&mut self) {
+		self.front = 1 - self.front
+	}
+}
+
+impl<T> PingPong<T>
+where
+	T: Bindable,
+{
+	/// Bind the front for reading at `read_unit`, and the back for writing at `write_unit`.
+	pub fn bind(&self, read_unit: u32, write_unit: u32) {
+		self.front().bind_read(read_unit);
+		self.back().bind_write(write_unit);
+	}
+
+	/// Bind only the front, for reading, e.g. for passes that use but do not update the state.
+	pub fn bind_front(&self, unit: u32) {
+		self.front().bind_read(unit)
+	}
+}
+
+impl<F> PingPong<Texture<F>>
+where
+	F: PixelFormat,
+{
+	/// Two 2D textures of given size.
+	#[track_caller]
+	pub fn new2d(size: uvec2) -> Self {
+		Self::new(Texture::new2d(size), Texture::new2d(size))
+	}
+
+	/// Two 2D textures, both holding `data`.
+	#[track_caller]
+	pub fn from_data2d(size: uvec2, data: &[F::Texel]) -> Self {
+		Self::new(Texture::from_data2d(size, data), Texture::from_data2d(size, data))
+	}
+
+	/// Size of the textures.
+	pub fn size(&self) -> uvec3 {
+		self.front().size()
+	}
+}
+
+impl<T> PingPong<Buffer<T>>
+where
+	T: Sized + Copy + 'static,
+{
+	/// Two buffers, both holding `data`.
+	#[track_caller]
+	pub fn from_data(data: &[T], flags: GLbitfield) -> Self {
+		Self::new(Buffer::new(data, flags), Buffer::new(data, flags))
+	}
+
+	/// Number of elements in each buffer.
+	pub fn len(&self) -> u32 {
+		self.front().len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
@@ -0,0 +1,69 @@
+use super::*;
+use gl_safe::*;
+
+/// A texture's pixel format. Fixes the internal format (used for storage and image unit bindings),
+/// the pixel format and type used to upload and read back texels, and the corresponding Rust texel type.
+///
+/// Used as marker type parameter of `Texture<F>`, so that mismatches are caught at compile time.
+pub trait PixelFormat: 'static {
+	/// Rust type of one texel, e.g. `vec2` for RG32F.
+	type Texel: Sized + Copy + Default + 'static;
+
+	/// Sized internal format, e.g. RG32F.
+	const INTERNAL_FORMAT: GLenum;
+
+	/// Pixel format for uploads and readbacks, e.g. RG.
+	const FORMAT: GLenum;
+
+	/// Pixel type for uploads and readbacks, e.g. FLOAT.
+	const TYPE: GLenum;
+}
+
+macro_rules! pixel_format {
+	($(#[$doc:meta])* $name:ident, $texel:ty, $internal:ident, $format:ident, $typ:ident) => {
+		$(#[$doc])*
+		pub struct $name;
+
+		impl PixelFormat for $name {
+			type Texel = $texel;
+			const INTERNAL_FORMAT: GLenum = gl::$internal;
+			const FORMAT: GLenum = gl::$format;
+			const TYPE: GLenum = gl::$typ;
+		}
+	};
+}
+
+pixel_format!(
+	/// One 32-bit float per texel.
+	R32f, f32, R32F, RED, FLOAT
+);
+
+pixel_format!(
+	/// Two 32-bit floats per texel, e.g. 2D positions.
+	Rg32f, vec2, RG32F, RG, FLOAT
+);
+
+pixel_format!(
+	/// Four 32-bit floats per texel.
+	Rgba32f, vec4, RGBA32F, RGBA, FLOAT
+);
+
+pixel_format!(
+	/// One 32-bit unsigned integer per texel, e.g. for atomic counters.
+	R32ui, u32, R32UI, RED_INTEGER, UNSIGNED_INT
+);
+
+pixel_format!(
+	/// Four 8-bit unsigned integers per texel (not normalized).
+	Rgba8ui, [u8; 4], RGBA8UI, RGBA_INTEGER, UNSIGNED_BYTE
+);
+
+pixel_format!(
+	/// Four 8-bit channels per texel, normalized to 0..1 when sampled.
+	Rgba8, [u8; 4], RGBA8, RGBA, UNSIGNED_BYTE
+);
+
+pixel_format!(
+	/// 8-bit sRGB color with linear alpha, e.g. for images loaded from file.
+	Srgb8Alpha8, [u8; 4], SRGB8_ALPHA8, RGBA, UNSIGNED_BYTE
+);
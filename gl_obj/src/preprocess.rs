@@ -0,0 +1,336 @@
+use super::*;
+use gl_safe::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Where the `Preprocessor` finds shader files (by path, e.g. "density.glsl").
+pub trait FileSystem {
+	/// Contents of the file at `path`, or an error message.
+	fn read(&self, path: &str) -> Result<String, String>;
+
+	/// Last modification time of the file at `path`, if known.
+	/// Used to reload shaders when their files change (see `HotProgram`).
+	fn modified(&self, _path: &str) -> Option<SystemTime> {
+		None
+	}
+}
+
+/// Shader files embedded in the binary, typically with `include_str!`,
+/// e.g. `EmbeddedFs::new().file("rgb.glsl", include_str!("rgb.glsl"))`.
+#[derive(Clone, Default)]
+pub struct EmbeddedFs {
+	files: HashMap<String, &'static str>,
+}
+
+impl EmbeddedFs {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a file called `path`.
+	pub fn file(mut self, path: &str, src: &'static str) -> Self {
+		self.files.insert(path.into(), src);
+		self
+	}
+}
+
+impl FileSystem for EmbeddedFs {
+	fn read(&self, path: &str) -> Result<String, String> {
+		match self.files.get(path) {
+			Some(src) => Ok(src.to_string()),
+			None => Err(format!("{}: no such embedded file", path)),
+		}
+	}
+}
+
+/// Shader files read from a directory on disk, e.g. for editing shaders without recompiling.
+#[derive(Clone)]
+pub struct DiskFs {
+	root: PathBuf,
+}
+
+impl DiskFs {
+	/// Files relative to directory `root`.
+	pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+		Self { root: root.into() }
+	}
+
+	/// The full path of `path`.
+	pub fn path(&self, path: &str) -> PathBuf {
+		self.root.join(path)
+	}
+}
+
+impl FileSystem for DiskFs {
+	fn read(&self, path: &str) -> Result<String, String> {
+		let full = self.path(path);
+		fs::read_to_string(&full).map_err(|e| format!("{}: {}", full.to_string_lossy(), e))
+	}
+
+	fn modified(&self, path: &str) -> Option<SystemTime> {
+		fs::metadata(self.path(path)).and_then(|m| m.modified()).ok()
+	}
+}
+
+/// Shader preprocessor: resolves `#include "file"` directives against a `FileSystem`,
+/// and injects `#define`s from Rust right after the `#version` line.
+///
+/// Each file is included at most once per shader (like `#pragma once`), so headers need no include guards.
+/// Include paths are relative to the root of the file system.
+pub struct Preprocessor {
+	fs: Box<dyn FileSystem>,
+	defines: Vec<(String, String)>,
+}
+
+impl Preprocessor {
+	pub fn new<F: FileSystem + 'static>(fs: F) -> Self {
+		Self {
+			fs: Box::new(fs),
+			defines: Vec::new(),
+		}
+	}
+
+	/// Inject `#define name value` into every shader.
+	pub fn define<V: fmt::Display>(mut self, name: &str, value: V) -> Self {
+		self.defines.push((name.into(), value.to_string()));
+		self
+	}
+
+	/// Last modification time of file `path`, if the file system knows it.
+	pub fn modified(&self, path: &str) -> Option<SystemTime> {
+		self.fs.modified(path)
+	}
+
+	/// The preprocessed source of file `path`.
+	pub fn process(&self, path: &str) -> Result<Source, String> {
+		let mut out = Source {
+			text: String::new(),
+			files: Vec::new(),
+			lines: Vec::new(),
+		};
+		self.include(&mut out, &mut HashSet::new(), path, true)?;
+		Ok(out)
+	}
+
+	/// Preprocess and compile file `path` into a shader of given type (e.g. COMPUTE_SHADER).
+	/// Line numbers in the error refer to the original files.
+	#[track_caller]
+	pub fn shader(&self, shader_type: GLenum, path: &str) -> Result<Shader, ShaderError> {
+		let src = self.process(path).map_err(|e| ShaderError::other(shader_type, path, e))?;
+		Shader::from_source(shader_type, &src)
+	}
+
+	fn include(&self, out: &mut Source, included: &mut HashSet<String>, path: &str, root: bool) -> Result<(), String> {
+		if !included.insert(path.into()) {
+			return Ok(());
+		}
+		let src = self.fs.read(path)?;
+		let file = out.files.len();
+		out.files.push(path.into());
+
+		let has_version = src.lines().any(|l| l.trim_start().starts_with("#version"));
+		if root && !has_version {
+			self.inject_defines(out);
+		}
+		for (i, line) in src.lines().enumerate() {
+			let lineno = (i + 1) as u32;
+			let directive = line.trim_start();
+			if let Some(arg) = directive.strip_prefix("#include") {
+				let name = parse_include(arg).ok_or_else(|| format!("{}:{}: malformed #include: {}", path, lineno, directive))?;
+				self.include(out, included, name, false).map_err(|e| format!("{}:{}: {}", path, lineno, e))?;
+				continue;
+			}
+			out.push(line, file, lineno);
+			if root && directive.starts_with("#version") {
+				self.inject_defines(out);
+			}
+		}
+		Ok(())
+	}
+
+	fn inject_defines(&self, out: &mut Source) {
+		if self.defines.is_empty() {
+			return;
+		}
+		let file = out.files.len();
+		out.files.push(DEFINES.into());
+		for (i, (name, value)) in self.defines.iter().enumerate() {
+			out.push(&format!("#define {} {}", name, value), file, (i + 1) as u32);
+		}
+	}
+}
+
+// Pseudo file name for the injected #defines.
+const DEFINES: &str = "<defines>";
+
+// `"name"` or `<name>` -> `name`.
+fn parse_include(arg: &str) -> Option<&str> {
+	let arg = arg.trim();
+	if arg.len() > 2 && (arg.starts_with('"') && arg.ends_with('"') || arg.starts_with('<') && arg.ends_with('>')) {
+		Some(&arg[1..arg.len() - 1])
+	} else {
+		None
+	}
+}
+
+/// Preprocessed shader source, which remembers the original file and line of each line.
+pub struct Source {
+	text: String,
+	files: Vec<String>,
+	lines: Vec<(usize, u32)>,
+}
+
+impl Source {
+	/// Source that is not preprocessed, e.g. from `include_str!`. `name` is used in error messages.
+	pub fn new(name: &str, text: &str) -> Self {
+		let mut src = Self {
+			text: String::new(),
+			files: vec![name.into()],
+			lines: Vec::new(),
+		};
+		for (i, line) in text.lines().enumerate() {
+			src.push(line, 0, (i + 1) as u32);
+		}
+		src
+	}
+
+	/// The preprocessed text, to be passed to the compiler.
+	pub fn text(&self) -> &str {
+		&self.text
+	}
+
+	/// Name of the file that was preprocessed.
+	pub fn name(&self) -> &str {
+		self.files.first().map(|s| s.as_str()).unwrap_or("")
+	}
+
+	/// The files that make up the source: the preprocessed file and everything it includes.
+	pub fn files(&self) -> Vec<&str> {
+		self.files.iter().map(|f| f.as_str()).filter(|&f| f != DEFINES).collect()
+	}
+
+	/// Original file name and line of preprocessed line `line` (both counting from 1).
+	pub fn origin(&self, line: u32) -> Option<(&str, u32)> {
+		let &(file, line) = self.lines.get((line as usize).checked_sub(1)?)?;
+		Some((&self.files[file], line))
+	}
+
+	/// Preprocessed line `line` (counting from 1).
+	pub fn line(&self, line: u32) -> Option<&str> {
+		self.text.lines().nth((line as usize).checked_sub(1)?)
+	}
+
+	/// Rewrite the line references in a compiler info log (e.g. "0:12(5): error ..." or "0(12) : error ...")
+	/// to the original "file:line".
+	pub fn map_log(&self, log: &str) -> String {
+		log.lines().map(|l| self.map_log_line(l)).collect::<Vec<_>>().join("\n")
+	}
+
+	fn map_log_line(&self, l: &str) -> String {
+		// Source string number 0, followed by ":line" (Mesa, AMD) or "(line)" (NVIDIA),
+		// at the start of the message or after e.g. "ERROR: ".
+		for (i, _) in l.match_indices('0') {
+			if i != 0 && !l[..i].ends_with(' ') {
+				continue;
+			}
+			let rest = &l[i + 1..];
+			let (open, close) = match rest.chars().next() {
+				Some(':') => (1, ""),
+				Some('(') => (1, ")"),
+				_ => continue,
+			};
+			let digits: String = rest[open..].chars().take_while(|c| c.is_ascii_digit()).collect();
+			let end = i + 1 + open + digits.len();
+			if digits.is_empty() || !l[end..].starts_with(close) {
+				continue;
+			}
+			if let Some((file, line)) = digits.parse().ok().and_then(|n| self.origin(n)) {
+				return format!("{}{}:{}{}", &l[..i], file, line, &l[end + close.len()..]);
+			}
+		}
+		l.into()
+	}
+
+	fn push(&mut self, line: &str, file: usize, lineno: u32) {
+		self.text.push_str(line);
+		self.text.push('\n');
+		self.lines.push((file, lineno));
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn preprocessor() -> Preprocessor {
+		let fs = EmbeddedFs::new()
+			.file("main.glsl", "#version 450\n#include \"a.glsl\"\n#include \"common.glsl\"\nvoid main() { x = a + b; }\n")
+			.file("a.glsl", "#include \"common.glsl\"\n#include <b.glsl>\nfloat a;\n")
+			.file("b.glsl", "float b;\n")
+			.file("common.glsl", "#define COMMON 1\n")
+			.file("bad.glsl", "#version 450\n#include common.glsl\n");
+		Preprocessor::new(fs).define("N", 64).define("DT", 0.01)
+	}
+
+	#[test]
+	fn nested_include_once() {
+		let src = preprocessor().process("main.glsl").unwrap();
+		assert_eq!(src.name(), "main.glsl");
+		assert_eq!(
+			src.text(),
+			"#version 450\n#define N 64\n#define DT 0.01\n#define COMMON 1\nfloat b;\nfloat a;\nvoid main() { x = a + b; }\n"
+		);
+		assert_eq!(src.origin(1), Some(("main.glsl", 1)));
+		assert_eq!(src.origin(4), Some(("common.glsl", 1)));
+		assert_eq!(src.origin(5), Some(("b.glsl", 1)));
+		assert_eq!(src.origin(6), Some(("a.glsl", 3)));
+		assert_eq!(src.origin(7), Some(("main.glsl", 4)));
+		assert_eq!(src.origin(8), None);
+		assert_eq!(src.origin(0), None);
+	}
+
+	#[test]
+	fn defines_after_version() {
+		let src = preprocessor().process("main.glsl").unwrap();
+		assert_eq!(src.origin(2), Some(("<defines>", 1)));
+		assert_eq!(src.origin(3), Some(("<defines>", 2)));
+
+		let src = preprocessor().process("b.glsl").unwrap();
+		assert_eq!(src.text(), "#define N 64\n#define DT 0.01\nfloat b;\n");
+		assert_eq!(src.origin(3), Some(("b.glsl", 1)));
+	}
+
+	#[test]
+	fn map_log() {
+		let src = preprocessor().process("main.glsl").unwrap();
+		// Mesa
+		assert_eq!(src.map_log("0:7(15): error: `x' undeclared"), "main.glsl:4(15): error: `x' undeclared");
+		assert_eq!(src.map_log("0:3(13): error: syntax error"), "<defines>:2(13): error: syntax error");
+		// NVIDIA
+		assert_eq!(src.map_log("0(6) : error C1008: undefined variable \"a\""), "a.glsl:3 : error C1008: undefined variable \"a\"");
+		// AMD
+		assert_eq!(src.map_log("ERROR: 0:5: 'b' : redefinition"), "ERROR: b.glsl:1: 'b' : redefinition");
+		// multiple lines, unknown lines and unrelated text are kept
+		assert_eq!(
+			src.map_log("0:7(1): error: a\n0:99(1): error: b\nlinking failed"),
+			"main.glsl:4(1): error: a\n0:99(1): error: b\nlinking failed"
+		);
+	}
+
+	#[test]
+	fn malformed_include() {
+		let err = preprocessor().process("bad.glsl").err().unwrap();
+		assert_eq!(err, "bad.glsl:2: malformed #include: #include common.glsl");
+	}
+
+	#[test]
+	fn missing_include() {
+		let fs = EmbeddedFs::new().file("main.glsl", "#include \"nope.glsl\"\n");
+		let err = Preprocessor::new(fs).process("main.glsl").err().unwrap();
+		assert_eq!(err, "main.glsl:1: nope.glsl: no such embedded file");
+	}
+}
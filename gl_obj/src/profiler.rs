@@ -0,0 +1,143 @@
+use super::*;
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+/// Measures the GPU time spent in named passes (e.g. "accel", "verlet").
+///
+/// Each pass is delimited by `begin` and `end`, which record GPU timestamps
+/// without waiting for the GPU. Results are picked up a few frames later by `collect`,
+/// and reported as a rolling average over the last `window` samples.
+/// Passes may be nested.
+///
+/// A disabled profiler (see `enabled`) issues no queries, so the calls can stay in place at no cost.
+pub struct GpuProfiler {
+	enabled: bool,
+	window: usize,
+	passes: Vec<Pass>,
+	open: Vec<(usize, Query)>,
+	pending: VecDeque<(usize, Query, Query)>,
+	free: Vec<Query>,
+}
+
+struct Pass {
+	name: String,
+	samples: VecDeque<u64>,
+}
+
+impl GpuProfiler {
+	/// A profiler reporting averages over the last `window` samples of each pass.
+	pub fn new(window: usize) -> Self {
+		Self {
+			enabled: true,
+			window: window.max(1),
+			passes: Vec::new(),
+			open: Vec::new(),
+			pending: VecDeque::new(),
+			free: Vec::new(),
+		}
+	}
+
+	/// Measure (the default), or make `begin`, `end` and `collect` do nothing.
+	pub fn enabled(mut self, enabled: bool) -> Self {
+		self.enabled = enabled;
+		self
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	/// Start timing the pass called `name`.
+	/// Must be matched by a call to `end`.
+	pub fn begin(&mut self, name: &str) {
+		if !self.enabled {
+			return;
+		}
+		let pass = self.pass_index(name);
+		let start = self.query();
+		start.timestamp();
+		self.open.push((pass, start));
+	}
+
+	/// Stop timing the pass most recently started with `begin`.
+	pub fn end(&mut self) {
+		if !self.enabled {
+			return;
+		}
+		let (pass, start) = self.open.pop().expect("GpuProfiler::end without begin");
+		let stop = self.query();
+		stop.timestamp();
+		self.pending.push_back((pass, start, stop));
+	}
+
+	/// Retrieve the results of all finished passes, without stalling the GPU.
+	/// Typically called once per frame.
+	pub fn collect(&mut self) {
+		if !self.enabled {
+			return;
+		}
+		// timestamps complete in submission order, so stop at the first unfinished pass.
+		while let Some((_, _, stop)) = self.pending.front() {
+			if !stop.result_available() {
+				break;
+			}
+			let (pass, start, stop) = self.pending.pop_front().unwrap();
+			let nanos = stop.result().saturating_sub(start.result());
+
+			let samples = &mut self.passes[pass].samples;
+			if samples.len() == self.window {
+				samples.pop_front();
+			}
+			samples.push_back(nanos);
+
+			self.free.push(start);
+			self.free.push(stop);
+		}
+	}
+
+	/// Rolling average GPU time of each pass, in order of first use.
+	/// Passes without results yet are omitted.
+	pub fn averages(&self) -> Vec<(&str, Duration)> {
+		self.passes
+			.iter()
+			.filter(|p| !p.samples.is_empty())
+			.map(|p| {
+				let avg = p.samples.iter().sum::<u64>() / (p.samples.len() as u64);
+				(p.name.as_str(), Duration::from_nanos(avg))
+			})
+			.collect()
+	}
+
+	/// Rolling average GPU time of the pass called `name`, if known.
+	pub fn average(&self, name: &str) -> Option<Duration> {
+		self.averages().into_iter().find(|(n, _)| *n == name).map(|(_, d)| d)
+	}
+
+	fn pass_index(&mut self, name: &str) -> usize {
+		match self.passes.iter().position(|p| p.name == name) {
+			Some(i) => i,
+			None => {
+				self.passes.push(Pass {
+					name: name.into(),
+					samples: VecDeque::with_capacity(self.window),
+				});
+				self.passes.len() - 1
+			}
+		}
+	}
+
+	fn query(&mut self) -> Query {
+		self.free.pop().unwrap_or_else(Query::new_timestamp)
+	}
+}
+
+impl fmt::Display for GpuProfiler {
+	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+		let width = self.passes.iter().map(|p| p.name.len()).max().unwrap_or(0);
+		for (name, avg) in self.averages() {
+			writeln!(f, "{:width$} {:8.3} ms", name, avg.as_secs_f64() * 1e3, width = width)?;
+		}
+		Ok(())
+	}
+}
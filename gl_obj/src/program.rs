@@ -1,28 +1,37 @@
 use super::*;
 use gl_safe::*;
 
-#[derive(Copy, Clone)]
-pub struct Program(pub GLuint);
+/// A program object. The GL program is deleted when the `Program` is dropped.
+pub struct Program(GLuint);
 
 impl Program {
 	/// Creates a program object.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glCreateProgram.xhtml
+	#[track_caller]
 	pub fn create() -> Self {
-		Self(glCreateProgram())
+		let handle = glCreateProgram();
+		registry::track("Program", handle);
+		Self(handle)
 	}
 
-	pub fn new(shaders: &[Shader]) -> Self {
-		let p = Self::create();
+	/// Link compiled shaders into a program, e.g. `Program::new(&[Shader::new_comp(src)?])`.
+	#[track_caller]
+	pub fn new(shaders: &[Shader]) -> Result<Self, ShaderError> {
+		let mut p = Self::create();
 		for s in shaders {
-			p.attach_shader(*s);
+			p = p.attach_shader(s);
 		}
-		p.link().expect("link program")
+		p.link().map_err(|log| ShaderError::link(&log))
+	}
+
+	pub fn handle(&self) -> GLuint {
+		self.0
 	}
 
 	/// Attaches a shader object to a program object.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glAttachShader.xhtml
-	pub fn attach_shader(self, shader: Shader) -> Self {
-		glAttachShader(self.0, shader.into());
+	pub fn attach_shader(self, shader: &Shader) -> Self {
+		glAttachShader(self.0, shader.handle());
 		self
 	}
 
@@ -31,6 +40,7 @@ impl Program {
 	#[must_use]
 	pub fn link(self) -> Result<Self, String> {
 		glLinkProgram(self.0);
+		forget_info(self.0);
 		let status = glGetProgramiv(self.0, gl::LINK_STATUS, 1)[0];
 		if status != (gl::TRUE as GLint) {
 			Err(self.info_log())
@@ -41,19 +51,19 @@ impl Program {
 
 	/// Returns a parameter from a program object.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetProgram.xhtml.
-	pub fn get_iv(self, pname: GLenum, n: usize) -> Vec<i32> {
+	pub fn get_iv(&self, pname: GLenum, n: usize) -> Vec<i32> {
 		glGetProgramiv(self.0, pname, n)
 	}
 
 	/// Returns the information log for a program object.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetProgramInfoLog.xhtml
-	pub fn info_log(self) -> String {
+	pub fn info_log(&self) -> String {
 		glGetProgramInfoLog(self.0)
 	}
 
 	/// Returns the location of an attribute variable.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetAttribLocation.xhtml
-	pub fn attrib_location(self, name: &str) -> Option<u32> {
+	pub fn attrib_location(&self, name: &str) -> Option<u32> {
 		let result = glGetAttribLocation(self.0, name);
 		if result < 0 {
 			None
@@ -64,7 +74,7 @@ impl Program {
 
 	/// Returns the location of a uniform variable.
 	/// http://docs.gl/gl4/glGetUniformLocation
-	pub fn uniform_location(self, name: &str) -> u32 {
+	pub fn uniform_location(&self, name: &str) -> u32 {
 		let result = glGetUniformLocation(self.0, name);
 		if result < 0 {
 			panic!("uniform location `{}` not found", name);
@@ -73,6 +83,56 @@ impl Program {
 		}
 	}
 
+	/// Returns the location of a uniform variable,
+	/// or None if it is not active (not declared, or optimized out by the compiler).
+	/// http://docs.gl/gl4/glGetUniformLocation
+	pub fn try_uniform_location(&self, name: &str) -> Option<u32> {
+		let result = glGetUniformLocation(self.0, name);
+		if result < 0 {
+			None
+		} else {
+			Some(result as u32)
+		}
+	}
+
+	/// Typed handle to uniform `name`, for setting its value without looking it up again.
+	/// Panics if the uniform is not active (not declared, or optimized out by the compiler),
+	/// or if its GLSL type does not match `T`.
+	#[track_caller]
+	pub fn uniform<T: UniformType>(&self, name: &str) -> Uniform<T> {
+		match Uniform::resolve(self.0, name) {
+			Ok(Some(u)) => u,
+			Ok(None) => panic!("uniform `{}` not active in program {} (not declared or optimized out)", name, self.0),
+			Err(e) => panic!("{}", e),
+		}
+	}
+
+	/// Like `uniform`, but only prints a warning if the uniform is not active,
+	/// in which case setting it does nothing. Still panics on a type mismatch.
+	#[track_caller]
+	pub fn optional_uniform<T: UniformType>(&self, name: &str) -> Uniform<T> {
+		match Uniform::resolve(self.0, name) {
+			Ok(Some(u)) => u,
+			Ok(None) => {
+				eprintln!("warning: uniform `{}` not active in program {} (not declared or optimized out)", name, self.0);
+				Uniform::inactive(self.0, name)
+			}
+			Err(e) => panic!("{}", e),
+		}
+	}
+
+	/// Active uniforms, blocks and images, e.g. for printing or validating bindings.
+	pub fn info(&self) -> ProgramInfo {
+		ProgramInfo::new(self.0)
+	}
+
+	/// Returns the handles of the shader objects attached to the program.
+	/// These remain valid while attached, even after their `Shader` has been dropped.
+	/// http://docs.gl/gl4/glGetAttachedShaders
+	pub fn attached_shaders(&self) -> Vec<GLuint> {
+		glGetAttachedShaders(self.0)
+	}
+
 	/// Query the index of a named resource within a program.
 	/// http://docs.gl/gl4/glGetProgramResourceIndex
 	pub fn resource_index(&self, interface: GLenum, name: &str) -> u32 {
@@ -84,109 +144,102 @@ impl Program {
 	}
 
 	// TODO: ivec3
-	pub fn compute_work_group_size(self) -> (u32, u32, u32) {
+	pub fn compute_work_group_size(&self) -> (u32, u32, u32) {
 		let s = self.get_iv(gl::COMPUTE_WORK_GROUP_SIZE, 3);
 		(s[0] as u32, s[1] as u32, s[2] as u32)
 	}
 
-	pub fn compute_and_sync(self, global_size: uvec3) {
-		glUseProgram(self.into());
+	/// Check that every image unit the program uses has a texture bound
+	/// with the declared format and a compatible access mode.
+	pub fn check_image_bindings(&self) -> Result<(), String> {
+		cached_info(self.0).check_image_bindings()
+	}
+
+	/// Run the compute program over `global_size` invocations and wait for its memory writes.
+	/// Fails without dispatching if the image bindings do not match the program (see `check_image_bindings`).
+	pub fn compute_and_sync(&self, global_size: uvec3) -> Result<(), String> {
+		self.check_image_bindings()?;
+		glUseProgram(self.0);
 		let wgs = self.compute_work_group_size();
 		glDispatchCompute(global_size.0 / wgs.0, global_size.1 / wgs.1, global_size.2 / wgs.2);
 		glMemoryBarrier(gl::ALL_BARRIER_BITS);
+		Ok(())
+	}
+
+	/// Run the compute program with the number of work groups read from `commands[index]`,
+	/// e.g. as produced by a previous pass (see `IndirectArgs`), and wait for its memory writes.
+	/// http://docs.gl/gl4/glDispatchComputeIndirect
+	pub fn compute_indirect_and_sync(&self, commands: &Buffer<DispatchIndirectCommand>, index: u32) -> Result<(), String> {
+		if index >= commands.len() {
+			return Err(format!("compute_indirect_and_sync: command {} out of bounds (len {})", index, commands.len()));
+		}
+		self.check_image_bindings()?;
+		glUseProgram(self.0);
+		glBindBuffer(gl::DISPATCH_INDIRECT_BUFFER, commands.handle());
+		glDispatchComputeIndirect(index as usize * std::mem::size_of::<DispatchIndirectCommand>());
+		glMemoryBarrier(gl::ALL_BARRIER_BITS);
+		Ok(())
 	}
 
 	/// Specify the value of a uniform variable for a specified program object.
 	/// http://docs.gl/gl4/glProgramUniform
-	pub fn uniform4f(self, location: u32, v0: f32, v1: f32, v2: f32, v3: f32) -> Self {
-		glUseProgram(self.0); // Weird that this is needed!
+	pub fn uniform4f(&self, location: u32, v0: f32, v1: f32, v2: f32, v3: f32) -> &Self {
 		glProgramUniform4f(self.0, location as i32, v0, v1, v2, v3);
 		self
 	}
 
 	/// Specify the value of a uniform variable for a specified program object.
 	/// http://docs.gl/gl4/glProgramUniform
-	pub fn uniform3f(self, location: u32, v0: f32, v1: f32, v2: f32) -> Self {
-		glUseProgram(self.0); // Weird that this is needed!
+	pub fn uniform3f(&self, location: u32, v0: f32, v1: f32, v2: f32) -> &Self {
 		glProgramUniform3f(self.0, location as i32, v0, v1, v2);
 		self
 	}
 
 	/// Specify the value of a uniform variable for a specified program object.
 	/// http://docs.gl/gl4/glProgramUniform
-	pub fn uniform2f(self, location: u32, v0: f32, v1: f32) -> Self {
-		glUseProgram(self.0); // Weird that this is needed!
+	pub fn uniform2f(&self, location: u32, v0: f32, v1: f32) -> &Self {
 		glProgramUniform2f(self.0, location as i32, v0, v1);
 		self
 	}
 
 	/// Specify the value of a uniform variable for a specified program object.
 	/// http://docs.gl/gl4/glProgramUniform
-	pub fn uniform1f(self, location: u32, v0: f32) -> Self {
-		glUseProgram(self.0); // Weird that this is needed!
+	pub fn uniform1f(&self, location: u32, v0: f32) -> &Self {
 		glProgramUniform1f(self.0, location as i32, v0);
 		self
 	}
 
 	/// Specify the value of a uniform variable for a specified program object.
 	/// http://docs.gl/gl4/glProgramUniform
-	pub fn uniform4i(self, location: u32, v0: i32, v1: i32, v2: i32, v3: i32) -> Self {
-		glUseProgram(self.0); // Weird that this is needed!
+	pub fn uniform4i(&self, location: u32, v0: i32, v1: i32, v2: i32, v3: i32) -> &Self {
 		glProgramUniform4i(self.0, location as i32, v0, v1, v2, v3);
 		self
 	}
 
 	/// Specify the value of a uniform variable for a specified program object.
 	/// http://docs.gl/gl4/glProgramUniform
-	pub fn uniform3i(self, location: u32, v0: i32, v1: i32, v2: i32) -> Self {
-		glUseProgram(self.0); // Weird that this is needed!
+	pub fn uniform3i(&self, location: u32, v0: i32, v1: i32, v2: i32) -> &Self {
 		glProgramUniform3i(self.0, location as i32, v0, v1, v2);
 		self
 	}
 
 	/// Specify the value of a uniform variable for a specified program object.
 	/// http://docs.gl/gl4/glProgramUniform
-	pub fn uniform2i(self, location: u32, v0: i32, v1: i32) -> Self {
-		glUseProgram(self.0); // Weird that this is needed!
+	pub fn uniform2i(&self, location: u32, v0: i32, v1: i32) -> &Self {
 		glProgramUniform2i(self.0, location as i32, v0, v1);
 		self
 	}
 
 	/// Specify the value of a uniform variable for a specified program object.
 	/// http://docs.gl/gl4/glProgramUniform
-	pub fn uniform1i(self, location: u32, v0: i32) -> Self {
-		glUseProgram(self.0); // Weird that this is needed!
+	pub fn uniform1i(&self, location: u32, v0: i32) -> &Self {
 		glProgramUniform1i(self.0, location as i32, v0);
 		self
 	}
 
-	pub fn set1f(self, attrib: &str, v: f32) -> Self {
-		let loc = self.uniform_location(attrib);
-		self.uniform1f(loc, v);
-		self
-	}
-
-	pub fn set2f(self, attrib: &str, v0: f32, v1: f32) -> Self {
-		let loc = self.uniform_location(attrib);
-		self.uniform2f(loc, v0, v1);
-		self
-	}
-
-	pub fn set1i(self, attrib: &str, v: i32) -> Self {
-		let loc = self.uniform_location(attrib);
-		self.uniform1i(loc, v);
-		self
-	}
-
-	pub fn set2i(self, attrib: &str, v0: i32, v1: i32) -> Self {
-		let loc = self.uniform_location(attrib);
-		self.uniform2i(loc, v0, v1);
-		self
-	}
-
 	/// Installs a program object as part of current rendering state.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glUseProgram.xhtml
-	pub fn use_program(self) {
+	pub fn use_program(&self) {
 		glUseProgram(self.0)
 	}
 
@@ -197,10 +250,22 @@ impl Program {
 		glShaderStorageBlockBinding(self.0, index, binding);
 		glBindBufferBase(gl::SHADER_STORAGE_BUFFER, binding, buffer.handle());
 	}
+
+	/// Assign binding point `binding` to uniform block `index`, and bind `buffer` to it.
+	/// See `UniformBuffer::bind` for a typed alternative.
+	pub fn bind_uniform_buffer<T>(&self, buffer: &Buffer<T>, index: u32, binding: u32)
+	where
+		T: Sized + Copy + 'static,
+	{
+		glUniformBlockBinding(self.0, index, binding);
+		glBindBufferBase(gl::UNIFORM_BUFFER, binding, buffer.handle());
+	}
 }
 
-impl Into<GLuint> for Program {
-	fn into(self) -> GLuint {
-		self.0
+impl Drop for Program {
+	fn drop(&mut self) {
+		registry::untrack("Program", self.0);
+		forget_info(self.0);
+		glDeleteProgram(self.0)
 	}
 }
@@ -0,0 +1,67 @@
+use super::*;
+use gl_safe::*;
+
+/// A query object, used to asynchronously retrieve information (like GPU time) from the GL server.
+pub struct Query {
+	handle: GLuint,
+	target: GLenum,
+}
+
+impl Query {
+	/// Create a query object.
+	/// http://docs.gl/gl4/glCreateQueries
+	#[track_caller]
+	pub fn create(target: GLenum) -> Self {
+		let handle = glCreateQuery(target);
+		registry::track("Query", handle);
+		Self { handle, target }
+	}
+
+	/// Create a query object that records the GPU time with `timestamp`.
+	#[track_caller]
+	pub fn new_timestamp() -> Self {
+		Self::create(gl::TIMESTAMP)
+	}
+
+	/// Start measuring (e.g. GL_TIME_ELAPSED, GL_SAMPLES_PASSED).
+	/// http://docs.gl/gl4/glBeginQuery
+	pub fn begin(&self) {
+		glBeginQuery(self.target, self.handle)
+	}
+
+	/// Stop measuring.
+	/// http://docs.gl/gl4/glBeginQuery
+	pub fn end(&self) {
+		glEndQuery(self.target)
+	}
+
+	/// Record the GPU time once all previous commands have completed.
+	/// http://docs.gl/gl4/glQueryCounter
+	pub fn timestamp(&self) {
+		glQueryCounter(self.handle, gl::TIMESTAMP)
+	}
+
+	/// Returns true if the result can be retrieved without stalling.
+	/// http://docs.gl/gl4/glGetQueryObject
+	pub fn result_available(&self) -> bool {
+		glGetQueryObjectiv(self.handle, gl::QUERY_RESULT_AVAILABLE) != 0
+	}
+
+	/// Returns the result (nanoseconds for time queries).
+	/// Blocks until the result is available, see `result_available`.
+	/// http://docs.gl/gl4/glGetQueryObject
+	pub fn result(&self) -> u64 {
+		glGetQueryObjectui64v(self.handle, gl::QUERY_RESULT)
+	}
+
+	pub fn handle(&self) -> GLuint {
+		self.handle
+	}
+}
+
+impl Drop for Query {
+	fn drop(&mut self) {
+		registry::untrack("Query", self.handle);
+		glDeleteQuery(self.handle)
+	}
+}
@@ -0,0 +1,44 @@
+/*
+	One pass of a binary radix sort on key-value pairs (see ComputeLib::sort_pairs).
+
+	Without SCATTER: flag the keys that have a 0 at `bit`.
+	With SCATTER: given the exclusive prefix sum of those flags, move keys with a 0 bit to the front
+	and keys with a 1 bit to the back, keeping the order within each (so the sort is stable).
+*/
+#version 450 core
+
+#define GROUP_SIZE 256
+
+layout (local_size_x = GROUP_SIZE) in;
+
+layout (std430, binding = 0) readonly buffer Keys   { uint keys[]; };
+layout (std430, binding = 1) readonly buffer Values { uint values[]; };
+#ifdef SCATTER
+layout (std430, binding = 2) readonly  buffer Offsets   { uint offsets[]; };
+layout (std430, binding = 3) writeonly buffer KeysOut   { uint keys_out[]; };
+layout (std430, binding = 4) writeonly buffer ValuesOut { uint values_out[]; };
+#else
+layout (std430, binding = 2) writeonly buffer Flags { uint flags[]; };
+#endif
+
+uniform uint n = 0;
+uniform uint bit = 0;
+
+uint is_zero(uint key) {
+	return ((key >> bit) & 1u) == 0u ? 1u : 0u;
+}
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+	if (i >= n) {
+		return;
+	}
+#ifdef SCATTER
+	uint zeros = offsets[n - 1] + is_zero(keys[n - 1]);
+	uint dst = is_zero(keys[i]) == 1 ? offsets[i] : zeros + i - offsets[i];
+	keys_out[dst] = keys[i];
+	values_out[dst] = values[i];
+#else
+	flags[i] = is_zero(keys[i]);
+#endif
+}
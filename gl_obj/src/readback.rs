@@ -0,0 +1,96 @@
+use super::*;
+use gl_safe::*;
+use std::collections::VecDeque;
+
+/// Copies buffer or texture contents to the CPU without stalling the pipeline.
+///
+/// A request (`read_buffer`, `read_texture`) only schedules a GPU-side copy into a staging buffer.
+/// The data becomes available through `try_recv` a few frames later, once the GPU has caught up.
+/// At most `depth` requests can be in flight, further requests are dropped until one is received.
+///
+/// Shaders writing the source must be followed by an appropriate `glMemoryBarrier`
+/// (e.g. `compute_and_sync`) before the request.
+pub struct AsyncReadback<T: Sized + Copy + 'static> {
+	len: u32,
+	free: Vec<Buffer<T>>,
+	pending: VecDeque<(Buffer<T>, Fence)>,
+}
+
+impl<T> AsyncReadback<T>
+where
+	T: Sized + Copy + 'static,
+{
+	/// Readback of `len` elements at a time, with up to `depth` requests in flight.
+	pub fn new(len: u32, depth: usize) -> Self {
+		Self {
+			len,
+			free: (0..depth.max(1)).map(|_| Buffer::new_uninit(len, gl::CLIENT_STORAGE_BIT)).collect(),
+			pending: VecDeque::new(),
+		}
+	}
+
+	/// Schedule a copy of `src`, which must have the length passed to `new`.
+	/// Returns false if the request was dropped because too many are already in flight.
+	pub fn read_buffer(&mut self, src: &Buffer<T>) -> bool {
+		if src.len() != self.len {
+			panic!("AsyncReadback::read_buffer: size mismatch: want len {}, have {}", self.len, src.len())
+		}
+		match self.free.pop() {
+			None => false,
+			Some(staging) => {
+				glCopyNamedBufferSubData(src.handle(), staging.handle(), 0, 0, src.bytes());
+				self.pending.push_back((staging, Fence::new()));
+				true
+			}
+		}
+	}
+
+	/// Schedule a copy of the base level of `src`.
+	/// The texture must have the number of texels passed to `new`.
+	/// Returns false if the request was dropped because too many are already in flight.
+	pub fn read_texture<F>(&mut self, src: &Texture<F>) -> bool
+	where
+		F: PixelFormat<Texel = T>,
+	{
+		if src.num_texels() != self.len as usize {
+			panic!("AsyncReadback::read_texture: size mismatch: want {} texels, have {}", self.len, src.num_texels())
+		}
+		match self.free.pop() {
+			None => false,
+			Some(staging) => {
+				glBindBuffer(gl::PIXEL_PACK_BUFFER, staging.handle());
+				glGetTextureImageOffset(src.handle(), 0, F::FORMAT, F::TYPE, staging.bytes(), 0);
+				glBindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+				self.pending.push_back((staging, Fence::new()));
+				true
+			}
+		}
+	}
+
+	/// Returns the oldest requested data, if the GPU has finished copying it.
+	/// Never blocks.
+	pub fn try_recv(&mut self) -> Option<Vec<T>> {
+		if !self.pending.front()?.1.is_signaled() {
+			return None;
+		}
+		let (staging, _) = self.pending.pop_front().unwrap();
+		let data = staging.get_data();
+		self.free.push(staging);
+		Some(data)
+	}
+
+	/// Returns the most recently requested data that is available,
+	/// discarding older results. Never blocks.
+	pub fn try_recv_latest(&mut self) -> Option<Vec<T>> {
+		let mut latest = None;
+		while let Some(data) = self.try_recv() {
+			latest = Some(data);
+		}
+		latest
+	}
+
+	/// Number of requests in flight.
+	pub fn pending(&self) -> usize {
+		self.pending.len()
+	}
+}
@@ -0,0 +1,54 @@
+/*
+	Reduce each block of GROUP_SIZE elements to one, with binary operation OP(a, b).
+	Repeated until one element is left (see ComputeLib::reduce).
+
+	Defines: T (element type), OP(a, b), IDENTITY (neutral element of OP),
+	and for reading from an image: FROM_IMAGE, IMAGE_TYPE, FORMAT, SWIZZLE.
+*/
+#version 450 core
+
+#define GROUP_SIZE 256
+
+layout (local_size_x = GROUP_SIZE) in;
+
+#ifdef FROM_IMAGE
+layout (binding = 0, FORMAT) uniform readonly IMAGE_TYPE src_img;
+#else
+layout (std430, binding = 0) readonly buffer Src { T src[]; };
+#endif
+layout (std430, binding = 1) writeonly buffer Dst { T dst[]; };
+
+uniform uint n = 0;
+
+shared T partial[GROUP_SIZE];
+
+T load(uint i) {
+	if (i >= n) {
+		return IDENTITY;
+	}
+#ifdef FROM_IMAGE
+	uint w = imageSize(src_img).x;
+	return imageLoad(src_img, ivec2(i % w, i / w))SWIZZLE;
+#else
+	return src[i];
+#endif
+}
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+	uint l = gl_LocalInvocationID.x;
+
+	partial[l] = load(i);
+	barrier();
+
+	for (uint s = GROUP_SIZE / 2; s > 0; s >>= 1) {
+		if (l < s) {
+			partial[l] = OP(partial[l], partial[l + s]);
+		}
+		barrier();
+	}
+
+	if (l == 0) {
+		dst[gl_WorkGroupID.x] = partial[0];
+	}
+}
@@ -0,0 +1,111 @@
+use gl_safe::*;
+#[cfg(debug_assertions)]
+use std::cell::RefCell;
+#[cfg(debug_assertions)]
+use std::collections::HashMap;
+#[cfg(debug_assertions)]
+use std::panic::Location;
+
+// Debug builds keep track of every GL object created through gl_obj (kind, handle and where it was created),
+// so that leaks and use of deleted objects can be reported at context teardown (see `report_live_objects`).
+// In release builds, tracking compiles to nothing.
+
+#[cfg(debug_assertions)]
+#[derive(Default)]
+struct Registry {
+	live: HashMap<(&'static str, GLuint), &'static Location<'static>>,
+	deleted: HashMap<(&'static str, GLuint), &'static Location<'static>>,
+	errors: Vec<String>,
+	torn_down: bool,
+}
+
+#[cfg(debug_assertions)]
+thread_local! {
+	static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+/// Record the creation of a GL object, at the caller's location.
+#[track_caller]
+pub(crate) fn track(kind: &'static str, handle: GLuint) {
+	#[cfg(debug_assertions)]
+	{
+		let location = Location::caller();
+		REGISTRY.with(|r| {
+			let mut r = r.borrow_mut();
+			if r.torn_down {
+				eprintln!("gl_obj: {} {} created at {} after context teardown", kind, handle, location);
+			}
+			r.deleted.remove(&(kind, handle)); // GL re-uses names
+			r.live.insert((kind, handle), location);
+		})
+	}
+	#[cfg(not(debug_assertions))]
+	let _ = (kind, handle);
+}
+
+/// Record the deletion of a GL object.
+pub(crate) fn untrack(kind: &'static str, handle: GLuint) {
+	#[cfg(debug_assertions)]
+	REGISTRY.with(|r| {
+		let mut r = r.borrow_mut();
+		match r.live.remove(&(kind, handle)) {
+			Some(location) => {
+				if r.torn_down {
+					eprintln!("gl_obj: {} {} (created at {}) deleted after context teardown", kind, handle, location);
+				}
+				r.deleted.insert((kind, handle), location);
+			}
+			None => {
+				let msg = format!("{} {} deleted twice, or not created through gl_obj", kind, handle);
+				r.errors.push(msg);
+			}
+		}
+	});
+	#[cfg(not(debug_assertions))]
+	let _ = (kind, handle);
+}
+
+/// Record an error if a raw `handle` refers to a GL object that has already been deleted.
+/// `what` describes the use, e.g. "ProgramInfo::new".
+pub(crate) fn check_live(kind: &'static str, handle: GLuint, what: &str) {
+	#[cfg(debug_assertions)]
+	REGISTRY.with(|r| {
+		let mut r = r.borrow_mut();
+		if let Some(location) = r.deleted.get(&(kind, handle)).copied() {
+			let msg = format!("{}: use of deleted {} {} (created at {})", what, kind, handle, location);
+			r.errors.push(msg);
+		}
+	});
+	#[cfg(not(debug_assertions))]
+	let _ = (kind, handle, what);
+}
+
+/// Print the GL objects that are still alive, with the location where they were created,
+/// and any use of deleted objects encountered so far. Returns the number of problems found.
+///
+/// To be called when the GL context is about to be destroyed, after dropping all GL objects.
+/// Only active in debug builds, in release builds this does nothing and returns 0.
+pub fn report_live_objects() -> usize {
+	#[cfg(debug_assertions)]
+	{
+		REGISTRY.with(|r| {
+			let mut r = r.borrow_mut();
+			r.torn_down = true;
+
+			let mut live: Vec<_> = r.live.iter().map(|(&(kind, handle), location)| (location.to_string(), kind, handle)).collect();
+			live.sort();
+			if !live.is_empty() {
+				eprintln!("gl_obj: {} GL object(s) leaked:", live.len());
+				for (location, kind, handle) in &live {
+					eprintln!("\t{} {} created at {}", kind, handle, location);
+				}
+			}
+			for err in &r.errors {
+				eprintln!("gl_obj: {}", err);
+			}
+			live.len() + r.errors.len()
+		})
+	}
+	#[cfg(not(debug_assertions))]
+	0
+}
@@ -0,0 +1,42 @@
+/*
+	Exclusive prefix sum within each block of GROUP_SIZE elements,
+	plus the total of each block (see ComputeLib::exclusive_scan).
+
+	Defines: T (element type).
+*/
+#version 450 core
+
+#define GROUP_SIZE 256
+
+layout (local_size_x = GROUP_SIZE) in;
+
+layout (std430, binding = 0) readonly  buffer Src  { T src[]; };
+layout (std430, binding = 1) writeonly buffer Dst  { T dst[]; };
+layout (std430, binding = 2) writeonly buffer Sums { T sums[]; };
+
+uniform uint n = 0;
+
+shared T partial[GROUP_SIZE];
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+	uint l = gl_LocalInvocationID.x;
+
+	partial[l] = i < n ? src[i] : T(0);
+	barrier();
+
+	// Hillis-Steele inclusive scan.
+	for (uint d = 1; d < GROUP_SIZE; d <<= 1) {
+		T x = l >= d ? partial[l - d] : T(0);
+		barrier();
+		partial[l] += x;
+		barrier();
+	}
+
+	if (i < n) {
+		dst[i] = l == 0 ? T(0) : partial[l - 1];
+	}
+	if (l == GROUP_SIZE - 1) {
+		sums[gl_WorkGroupID.x] = partial[l];
+	}
+}
@@ -0,0 +1,23 @@
+/*
+	Second half of a multi-block scan:
+	add the (scanned) block totals to each element of the block.
+
+	Defines: T (element type).
+*/
+#version 450 core
+
+#define GROUP_SIZE 256
+
+layout (local_size_x = GROUP_SIZE) in;
+
+layout (std430, binding = 0) readonly buffer Offsets { T offsets[]; };
+layout (std430, binding = 1)          buffer Dst     { T dst[]; };
+
+uniform uint n = 0;
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+	if (i < n) {
+		dst[i] += offsets[gl_WorkGroupID.x];
+	}
+}
@@ -1,26 +1,47 @@
 use super::*;
 use gl_safe::*;
 
-#[derive(Copy, Clone)]
-pub struct Shader(pub GLuint);
+/// A shader object. The GL shader is deleted when the `Shader` is dropped,
+/// which is fine as soon as it is attached to a program.
+pub struct Shader(GLuint);
 
 impl Shader {
 	/// Creates a shader object.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glCreateShader.xhtml
+	#[track_caller]
 	pub fn create(shader_type: GLenum) -> Self {
-		Self(glCreateShader(shader_type))
+		let handle = glCreateShader(shader_type);
+		registry::track("Shader", handle);
+		Self(handle)
 	}
 
-	pub fn new_vert(src: &str) -> Self {
-		Self::create(gl::VERTEX_SHADER).source(src).compile().expect("compile vertex sharder")
+	/// Compile a vertex shader from source code (not preprocessed).
+	#[track_caller]
+	pub fn new_vert(src: &str) -> Result<Self, ShaderError> {
+		Self::from_source(gl::VERTEX_SHADER, &Source::new("<string>", src))
 	}
 
-	pub fn new_frag(src: &str) -> Self {
-		Self::create(gl::FRAGMENT_SHADER).source(src).compile().expect("compile fragment sharder")
+	/// Compile a fragment shader from source code (not preprocessed).
+	#[track_caller]
+	pub fn new_frag(src: &str) -> Result<Self, ShaderError> {
+		Self::from_source(gl::FRAGMENT_SHADER, &Source::new("<string>", src))
 	}
 
-	pub fn new_comp(src: &str) -> Self {
-		Self::create(gl::COMPUTE_SHADER).source(src).compile().expect("compile compute sharder")
+	/// Compile a compute shader from source code (not preprocessed).
+	#[track_caller]
+	pub fn new_comp(src: &str) -> Result<Self, ShaderError> {
+		Self::from_source(gl::COMPUTE_SHADER, &Source::new("<string>", src))
+	}
+
+	pub fn handle(&self) -> GLuint {
+		self.0
+	}
+
+	/// Compile a preprocessed shader (see `Preprocessor`).
+	/// Line numbers in the error refer to the original files.
+	#[track_caller]
+	pub fn from_source(shader_type: GLenum, src: &Source) -> Result<Self, ShaderError> {
+		Self::create(shader_type).source(src.text()).compile().map_err(|log| ShaderError::compile(shader_type, src, &log))
 	}
 
 	/// Replaces the source code in a shader object.
@@ -46,20 +67,32 @@ impl Shader {
 	/// Returns a parameter from a shader object.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetShader.xhtml
 	/// TODO: iv is vector!
-	pub fn get_iv(self, pname: GLenum) -> i32 {
+	pub fn get_iv(&self, pname: GLenum) -> i32 {
 		glGetShaderiv(self.0, pname)
 	}
 
+	/// Returns the shader type, e.g. COMPUTE_SHADER.
+	pub fn shader_type(&self) -> GLenum {
+		self.get_iv(gl::SHADER_TYPE) as GLenum
+	}
+
+	/// Returns the source code of a shader object.
+	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetShaderSource.xhtml
+	pub fn get_source(&self) -> String {
+		glGetShaderSource(self.0)
+	}
+
 	/// Returns the information log for a shader object.
 	/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetShaderInfoLog.xhtml
 	#[allow(non_snake_case)]
-	pub fn info_log(self) -> String {
+	pub fn info_log(&self) -> String {
 		glGetShaderInfoLog(self.0)
 	}
 }
 
-impl Into<GLuint> for Shader {
-	fn into(self) -> GLuint {
-		self.0
+impl Drop for Shader {
+	fn drop(&mut self) {
+		registry::untrack("Shader", self.0);
+		glDeleteShader(self.0)
 	}
 }
@@ -0,0 +1,325 @@
+use super::*;
+use gl_safe::*;
+use std::error::Error;
+use std::fmt;
+
+/// A programmable stage of the pipeline, e.g. for error messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderStage {
+	Vertex,
+	TessControl,
+	TessEvaluation,
+	Geometry,
+	Fragment,
+	Compute,
+}
+
+impl ShaderStage {
+	/// The stage of a shader type, e.g. COMPUTE_SHADER.
+	pub fn from_gl(shader_type: GLenum) -> Option<Self> {
+		use ShaderStage::*;
+		match shader_type {
+			gl::VERTEX_SHADER => Some(Vertex),
+			gl::TESS_CONTROL_SHADER => Some(TessControl),
+			gl::TESS_EVALUATION_SHADER => Some(TessEvaluation),
+			gl::GEOMETRY_SHADER => Some(Geometry),
+			gl::FRAGMENT_SHADER => Some(Fragment),
+			gl::COMPUTE_SHADER => Some(Compute),
+			_ => None,
+		}
+	}
+
+	/// The shader type, e.g. COMPUTE_SHADER.
+	pub fn gl_enum(self) -> GLenum {
+		use ShaderStage::*;
+		match self {
+			Vertex => gl::VERTEX_SHADER,
+			TessControl => gl::TESS_CONTROL_SHADER,
+			TessEvaluation => gl::TESS_EVALUATION_SHADER,
+			Geometry => gl::GEOMETRY_SHADER,
+			Fragment => gl::FRAGMENT_SHADER,
+			Compute => gl::COMPUTE_SHADER,
+		}
+	}
+}
+
+impl fmt::Display for ShaderStage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		use ShaderStage::*;
+		let name = match self {
+			Vertex => "vertex shader",
+			TessControl => "tessellation control shader",
+			TessEvaluation => "tessellation evaluation shader",
+			Geometry => "geometry shader",
+			Fragment => "fragment shader",
+			Compute => "compute shader",
+		};
+		f.write_str(name)
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+	Error,
+	Warning,
+}
+
+impl fmt::Display for Severity {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Severity::Error => f.write_str("error"),
+			Severity::Warning => f.write_str("warning"),
+		}
+	}
+}
+
+/// One message from the shader compiler, located in the original (not preprocessed) source.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+	pub file: String,
+	pub line: u32,
+	/// Not all drivers report columns.
+	pub column: Option<u32>,
+	pub severity: Severity,
+	pub message: String,
+	/// The offending line and a few lines before it, with their line numbers.
+	pub context: Vec<(u32, String)>,
+}
+
+/// A failure to build a shader or program: compilation, linking or preprocessing (e.g. a missing include).
+///
+/// Displays like a Rust compiler error, with source context, e.g.:
+///
+/// ```text
+/// error: 'foo' : undeclared identifier
+///   --> density.glsl:21:2 (compute shader)
+///    |
+/// 20 |     ivec2 size = imageSize(photons);
+/// 21 |     foo = 1;
+///    |     ^
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShaderError {
+	/// None for link errors.
+	pub stage: Option<ShaderStage>,
+	/// The file name of the shader, or e.g. "<string>".
+	pub source_name: String,
+	/// Messages parsed from the driver's log. May be empty if the log format was not recognized.
+	pub diagnostics: Vec<Diagnostic>,
+	/// The driver's info log (line numbers referring to the original files where possible).
+	pub log: String,
+}
+
+impl ShaderError {
+	/// An error from compiling `src`, with the driver's info `log`.
+	pub fn compile(shader_type: GLenum, src: &Source, log: &str) -> Self {
+		let diagnostics = log.lines().filter_map(parse_log_line).filter_map(|(line, column, severity, message)| {
+			let (file, orig_line) = src.origin(line)?;
+			let context = (line.saturating_sub(2)..=line)
+				.filter_map(|l| match src.origin(l) {
+					Some((f, n)) if f == file => Some((n, src.line(l)?.to_string())),
+					_ => None,
+				})
+				.collect();
+			Some(Diagnostic {
+				file: file.into(),
+				line: orig_line,
+				column,
+				severity,
+				message,
+				context,
+			})
+		});
+		Self {
+			stage: ShaderStage::from_gl(shader_type),
+			source_name: src.name().into(),
+			diagnostics: diagnostics.collect(),
+			log: src.map_log(log),
+		}
+	}
+
+	/// An error from linking a program.
+	pub fn link(log: &str) -> Self {
+		Self {
+			stage: None,
+			source_name: "<program>".into(),
+			diagnostics: Vec::new(),
+			log: log.into(),
+		}
+	}
+
+	/// Any other error, e.g. from the preprocessor.
+	pub fn other(shader_type: GLenum, source_name: &str, message: String) -> Self {
+		Self {
+			stage: ShaderStage::from_gl(shader_type),
+			source_name: source_name.into(),
+			diagnostics: Vec::new(),
+			log: message,
+		}
+	}
+
+	/// Whether any diagnostic is an error (rather than a warning).
+	pub fn has_errors(&self) -> bool {
+		self.diagnostics.iter().any(|d| d.severity == Severity::Error)
+	}
+}
+
+impl fmt::Display for ShaderError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let what = match self.stage {
+			Some(stage) => stage.to_string(),
+			None => "program".into(),
+		};
+		if self.diagnostics.is_empty() {
+			return write!(f, "error: {} {}:\n{}", what, self.source_name, self.log.trim_end());
+		}
+		for (i, d) in self.diagnostics.iter().enumerate() {
+			if i != 0 {
+				writeln!(f)?;
+			}
+			let width = d.context.iter().map(|(n, _)| n.to_string().len()).max().unwrap_or(1);
+			let pad = " ".repeat(width);
+			writeln!(f, "{}: {}", d.severity, d.message)?;
+			match d.column {
+				Some(col) => writeln!(f, "{}--> {}:{}:{} ({})", pad, d.file, d.line, col, what)?,
+				None => writeln!(f, "{}--> {}:{} ({})", pad, d.file, d.line, what)?,
+			}
+			writeln!(f, "{} |", pad)?;
+			for (n, text) in &d.context {
+				writeln!(f, "{:>width$} | {}", n, text, width = width)?;
+				if *n == d.line {
+					writeln!(f, "{} | {}^", pad, caret_indent(text, d.column))?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+impl Error for ShaderError {}
+
+// Whitespace to put under `text` to reach `column` (counting from 1),
+// or the first non-blank character if the column is unknown. Tabs are kept so that the caret lines up.
+fn caret_indent(text: &str, column: Option<u32>) -> String {
+	let n = match column {
+		Some(c) => (c as usize).saturating_sub(1),
+		None => text.len() - text.trim_start().len(),
+	};
+	text.chars().take(n).map(|c| if c == '\t' { '\t' } else { ' ' }).collect()
+}
+
+// Parse one line of a compiler log, in any of these formats:
+//
+// 	0:12(5): error: message            (Mesa)
+// 	0(12) : error C1008: message       (NVIDIA)
+// 	ERROR: 0:12: message               (AMD)
+//
+// into (line, column, severity, message).
+fn parse_log_line(l: &str) -> Option<(u32, Option<u32>, Severity, String)> {
+	let l = l.trim();
+
+	// AMD
+	for (prefix, severity) in &[("ERROR:", Severity::Error), ("WARNING:", Severity::Warning)] {
+		if let Some(rest) = l.strip_prefix(prefix) {
+			let rest = rest.trim_start().strip_prefix("0:")?;
+			let (line, rest) = split_number(rest)?;
+			let message = rest.strip_prefix(':')?.trim();
+			return Some((line, None, *severity, message.into()));
+		}
+	}
+
+	// Mesa, NVIDIA
+	let rest = l.strip_prefix('0')?;
+	let (line, column, rest) = if let Some(rest) = rest.strip_prefix(':') {
+		let (line, rest) = split_number(rest)?;
+		match rest.strip_prefix('(') {
+			Some(rest) => {
+				let (col, rest) = split_number(rest)?;
+				(line, Some(col), rest.strip_prefix(')')?)
+			}
+			None => (line, None, rest),
+		}
+	} else {
+		let (line, rest) = split_number(rest.strip_prefix('(')?)?;
+		(line, None, rest.strip_prefix(')')?)
+	};
+	let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+	let (severity, rest) = if let Some(rest) = rest.strip_prefix("error") {
+		(Severity::Error, rest)
+	} else if let Some(rest) = rest.strip_prefix("warning") {
+		(Severity::Warning, rest)
+	} else {
+		return None;
+	};
+	// skip NVIDIA's error code, e.g. "C1008"
+	let message = match rest.find(':') {
+		Some(i) => &rest[i + 1..],
+		None => rest,
+	};
+	Some((line, column, severity, message.trim().into()))
+}
+
+// "12rest" -> (12, "rest")
+fn split_number(s: &str) -> Option<(u32, &str)> {
+	let n = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+	Some((s[..n].parse().ok()?, &s[n..]))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn parse_mesa() {
+		let l = "0:12(5): error: `foo' undeclared";
+		assert_eq!(parse_log_line(l), Some((12, Some(5), Severity::Error, "`foo' undeclared".into())));
+		let l = "0:3(10): warning: `x' used uninitialized";
+		assert_eq!(parse_log_line(l), Some((3, Some(10), Severity::Warning, "`x' used uninitialized".into())));
+	}
+
+	#[test]
+	fn parse_nvidia() {
+		let l = "0(12) : error C1008: undefined variable \"foo\"";
+		assert_eq!(parse_log_line(l), Some((12, None, Severity::Error, "undefined variable \"foo\"".into())));
+	}
+
+	#[test]
+	fn parse_amd() {
+		let l = "ERROR: 0:12: 'foo' : undeclared identifier";
+		assert_eq!(parse_log_line(l), Some((12, None, Severity::Error, "'foo' : undeclared identifier".into())));
+		assert_eq!(parse_log_line("ERROR: 1 compilation errors.  No code generated."), None);
+	}
+
+	#[test]
+	fn parse_other() {
+		assert_eq!(parse_log_line(""), None);
+		assert_eq!(parse_log_line("error: linking failed"), None);
+	}
+
+	#[test]
+	fn caret() {
+		assert_eq!(caret_indent("\tfoo = 1;", Some(2)), "\t");
+		assert_eq!(caret_indent("\t  foo = 1;", Some(6)), "\t    ");
+		assert_eq!(caret_indent("\t  foo = 1;", None), "\t  ");
+		assert_eq!(caret_indent("foo = 1;", None), "");
+	}
+
+	#[test]
+	fn display() {
+		let src = Source::new("density.glsl", "#version 450\nvoid main() {\n\tivec2 size = imageSize(photons);\n\tfoo = 1;\n}\n");
+		let err = ShaderError::compile(gl::COMPUTE_SHADER, &src, "0:4(2): error: `foo' undeclared\n");
+		assert!(err.has_errors());
+		assert_eq!(err.log, "density.glsl:4(2): error: `foo' undeclared");
+		let expected = [
+			"error: `foo' undeclared",
+			" --> density.glsl:4:2 (compute shader)",
+			"  |",
+			"2 | void main() {",
+			"3 | \tivec2 size = imageSize(photons);",
+			"4 | \tfoo = 1;",
+			"  | \t^",
+			"",
+		];
+		assert_eq!(err.to_string(), expected.join("\n"));
+	}
+}
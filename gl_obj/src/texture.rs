@@ -1,87 +1,123 @@
 use super::*;
 use gl_safe::*;
+use std::marker::PhantomData;
 
-#[derive(Copy, Clone)]
-pub struct Texture {
+/// A texture object with pixel format `F` (e.g. `Rg32f`), which fixes the internal format and the texel type.
+/// The GL texture is deleted when the `Texture` is dropped.
+pub struct Texture<F: PixelFormat> {
 	handle: GLuint,
-	internalformat: GLenum,
 	size: uvec3,
 	dimensionality: u8,
+	_format: PhantomData<F>,
 }
 
-impl Texture {
-	/// Create a buffer object.
+impl<F> Texture<F>
+where
+	F: PixelFormat,
+{
+	/// Create a texture object.
 	/// http://docs.gl/gl4/glCreateTextures
+	#[track_caller]
 	pub fn create(target: GLenum) -> Self {
+		let handle = glCreateTexture(target);
+		registry::track("Texture", handle);
 		Self {
-			handle: glCreateTexture(target),
-			internalformat: 0,
+			handle,
 			size: uvec3(0, 0, 0),
 			dimensionality: 0,
+			_format: PhantomData,
 		}
 	}
 
-	pub fn new1d(internalformat: GLenum, width: u32) -> Self {
-		Self::create(gl::TEXTURE_1D).storage1d(1, internalformat, width)
+	#[track_caller]
+	pub fn new1d(width: u32) -> Self {
+		Self::create(gl::TEXTURE_1D).storage1d(1, width)
 	}
 
-	pub fn new2d(internalformat: GLenum, size: uvec2) -> Self {
-		Self::create(gl::TEXTURE_2D).storage2d(1, internalformat, size.0, size.1)
+	#[track_caller]
+	pub fn new2d(size: uvec2) -> Self {
+		Self::create(gl::TEXTURE_2D).storage2d(1, size.0, size.1)
 	}
 
-	pub fn new3d(internalformat: GLenum, size: uvec3) -> Self {
-		Self::create(gl::TEXTURE_3D).storage3d(1, internalformat, size.0, size.1, size.2)
+	#[track_caller]
+	pub fn new3d(size: uvec3) -> Self {
+		Self::create(gl::TEXTURE_3D).storage3d(1, size.0, size.1, size.2)
+	}
+
+	/// A 2D texture holding `data`, row by row.
+	#[track_caller]
+	pub fn from_data2d(size: uvec2, data: &[F::Texel]) -> Self {
+		Self::new2d(size).sub_image2d(0, 0, 0, size.0, size.1, data)
 	}
 
 	/// Simultaneously specify storage for all levels of a one-dimensional texture.
 	/// http://docs.gl/gl4/glTexStorage1D
 	#[allow(non_snake_case)]
-	pub fn storage1d(self, levels: u32, internalformat: GLenum, width: u32) -> Self {
-		glTextureStorage1D(self.handle, levels as i32, internalformat, width as i32);
-		Self {
-			internalformat,
-			dimensionality: 1,
-			size: uvec3(width, 0, 0),
-			..self
-		}
+	pub fn storage1d(mut self, levels: u32, width: u32) -> Self {
+		glTextureStorage1D(self.handle, levels as i32, F::INTERNAL_FORMAT, width as i32);
+		self.dimensionality = 1;
+		self.size = uvec3(width, 0, 0);
+		self
 	}
 
 	/// Simultaneously specify storage for all levels of a two-dimensional or one-dimensional array texture.
 	/// http://docs.gl/gl4/glTexStorage2D
 	#[allow(non_snake_case)]
-	pub fn storage2d(self, levels: i32, internalformat: GLenum, width: u32, height: u32) -> Self {
-		glTextureStorage2D(self.handle, levels, internalformat, width as i32, height as i32);
-		Self {
-			internalformat,
-			dimensionality: 2,
-			size: uvec3(width, height, 0),
-			..self
-		}
+	pub fn storage2d(mut self, levels: i32, width: u32, height: u32) -> Self {
+		glTextureStorage2D(self.handle, levels, F::INTERNAL_FORMAT, width as i32, height as i32);
+		self.dimensionality = 2;
+		self.size = uvec3(width, height, 0);
+		self
 	}
 
 	/// Simultaneously specify storage for all levels of a three-dimensional, two-dimensional array or cube-map array texture.
 	/// http://docs.gl/gl4/glTexStorage3D
 	#[allow(non_snake_case)]
-	pub fn storage3d(self, levels: i32, internalformat: GLenum, width: u32, height: u32, depth: u32) -> Self {
-		glTextureStorage3D(self.handle, levels, internalformat, width as i32, height as i32, depth as i32);
-		Self {
-			internalformat,
-			dimensionality: 3,
-			size: uvec3(width, height, depth),
-			..self
-		}
+	pub fn storage3d(mut self, levels: i32, width: u32, height: u32, depth: u32) -> Self {
+		glTextureStorage3D(self.handle, levels, F::INTERNAL_FORMAT, width as i32, height as i32, depth as i32);
+		self.dimensionality = 3;
+		self.size = uvec3(width, height, depth);
+		self
 	}
 
 	pub fn internalformat(&self) -> GLenum {
-		self.internalformat
+		F::INTERNAL_FORMAT
 	}
 
 	pub fn size(&self) -> uvec3 {
 		self.size
 	}
 
+	pub fn handle(&self) -> GLuint {
+		self.handle
+	}
+
+	/// Number of texels in the base level.
+	pub fn num_texels(&self) -> usize {
+		self.dims().iter().map(|&d| d as usize).product()
+	}
+
+	// Size as passed to glTextureStorage1/2/3D.
+	fn dims(&self) -> Vec<i32> {
+		let s = self.size;
+		match self.dimensionality {
+			1 => vec![s.0 as i32],
+			2 => vec![s.0 as i32, s.1 as i32],
+			_ => vec![s.0 as i32, s.1 as i32, s.2 as i32],
+		}
+	}
+
+	/// Return a copy of the base level.
+	/// Blocks until the GPU is done writing the texture, see `AsyncReadback` for a non-blocking alternative.
+	/// http://docs.gl/gl4/glGetTexImage
+	pub fn get_image(&self) -> Vec<F::Texel> {
+		let mut pixels = vec![F::Texel::default(); self.num_texels()];
+		glGetTextureImage(self.handle, 0, &self.dims(), F::FORMAT, F::TYPE, &mut pixels);
+		pixels
+	}
+
 	pub fn bind_image_unit(&self, unit: u32, access: GLenum) {
-		glBindImageTexture(unit, self.handle, 0, false, 0, access, self.internalformat());
+		glBindImageTexture(unit, self.handle, 0, false, 0, access, F::INTERNAL_FORMAT);
 	}
 
 	pub fn bind_texture_unit(&self, unit: u32) {
@@ -90,30 +126,21 @@ impl Texture {
 
 	/// Specify a one-dimensional texture subimage.
 	/// http://docs.gl/gl4/glTexSubImage1D
-	pub fn sub_image1d<T>(self, level: i32, xoffset: i32, width: i32, format: GLenum, typ: GLenum, pixels: &[T]) -> Self
-	where
-		T: Sized + Copy + 'static,
-	{
-		glTextureSubImage1D(self.handle, level, xoffset, width, format, typ, pixels);
+	pub fn sub_image1d(self, level: i32, xoffset: i32, width: i32, pixels: &[F::Texel]) -> Self {
+		glTextureSubImage1D(self.handle, level, xoffset, width, F::FORMAT, F::TYPE, pixels);
 		self
 	}
 
 	/// Specify a two-dimensional texture subimage.
 	/// http://docs.gl/gl4/glTexSubImage2D
-	pub fn sub_image2d<T>(self, level: u32, xoffset: u32, yoffset: u32, width: u32, height: u32, format: GLenum, typ: GLenum, pixels: &[T]) -> Self
-	where
-		T: Sized + Copy + 'static,
-	{
-		glTextureSubImage2D(self.handle, level as i32, xoffset as i32, yoffset as i32, width as i32, height as i32, format, typ, pixels);
+	pub fn sub_image2d(self, level: u32, xoffset: u32, yoffset: u32, width: u32, height: u32, pixels: &[F::Texel]) -> Self {
+		glTextureSubImage2D(self.handle, level as i32, xoffset as i32, yoffset as i32, width as i32, height as i32, F::FORMAT, F::TYPE, pixels);
 		self
 	}
 
 	/// Specify a three-dimensional texture subimage.
 	/// http://docs.gl/gl4/glTexSubImage3D
-	pub fn sub_image3d<T>(self, level: u32, xoffset: u32, yoffset: u32, zoffset: u32, width: u32, height: u32, depth: u32, format: GLenum, typ: GLenum, pixels: &[T]) -> Self
-	where
-		T: Sized + Copy + 'static,
-	{
+	pub fn sub_image3d(self, level: u32, xoffset: u32, yoffset: u32, zoffset: u32, width: u32, height: u32, depth: u32, pixels: &[F::Texel]) -> Self {
 		glTextureSubImage3D(
 			self.handle,
 			level as i32,
@@ -123,8 +150,8 @@ impl Texture {
 			width as i32,
 			height as i32,
 			depth as i32,
-			format,
-			typ,
+			F::FORMAT,
+			F::TYPE,
 			pixels,
 		);
 		self
@@ -183,8 +210,12 @@ impl Texture {
 	}
 }
 
-impl Into<GLuint> for Texture {
-	fn into(self) -> GLuint {
-		self.handle
+impl<F> Drop for Texture<F>
+where
+	F: PixelFormat,
+{
+	fn drop(&mut self) {
+		registry::untrack("Texture", self.handle);
+		glDeleteTexture(self.handle)
 	}
 }
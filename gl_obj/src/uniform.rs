@@ -0,0 +1,159 @@
+use super::*;
+use gl_safe::*;
+use std::marker::PhantomData;
+
+/// A Rust type that can be stored in a GLSL uniform variable.
+pub trait UniformType: Sized + Copy + 'static {
+	/// GLSL type of the uniform, as reported by introspection (e.g. FLOAT_VEC2 for vec2).
+	const GL_TYPE: GLenum;
+
+	/// Whether a uniform of GLSL type `typ` can be set from `Self`.
+	fn accepts(typ: GLenum) -> bool {
+		typ == Self::GL_TYPE
+	}
+
+	/// Set uniform `location` of `program` (an array if more than one value).
+	fn set(program: GLuint, location: i32, values: &[Self]);
+}
+
+macro_rules! uniform_type {
+	($t:ty, $gl_type:ident, $func:ident) => {
+		impl UniformType for $t {
+			const GL_TYPE: GLenum = gl::$gl_type;
+			fn set(program: GLuint, location: i32, values: &[Self]) {
+				$func(program, location, values)
+			}
+		}
+	};
+	($t:ty, $gl_type:ident, $func:ident, |$v:pat| $conv:expr) => {
+		impl UniformType for $t {
+			const GL_TYPE: GLenum = gl::$gl_type;
+			fn set(program: GLuint, location: i32, values: &[Self]) {
+				let values: Vec<_> = values.iter().map(|$v| $conv).collect();
+				$func(program, location, &values)
+			}
+		}
+	};
+}
+
+uniform_type!(f32, FLOAT, glProgramUniform1fv);
+uniform_type!(vec2, FLOAT_VEC2, glProgramUniform2fv, |v| [v.0, v.1]);
+uniform_type!(vec3, FLOAT_VEC3, glProgramUniform3fv, |v| [v.0, v.1, v.2]);
+uniform_type!(vec4, FLOAT_VEC4, glProgramUniform4fv, |v| [v.0, v.1, v.2, v.3]);
+uniform_type!(ivec2, INT_VEC2, glProgramUniform2iv, |v| [v.0, v.1]);
+uniform_type!(ivec3, INT_VEC3, glProgramUniform3iv, |v| [v.0, v.1, v.2]);
+uniform_type!(ivec4, INT_VEC4, glProgramUniform4iv, |v| [v.0, v.1, v.2, v.3]);
+uniform_type!(u32, UNSIGNED_INT, glProgramUniform1uiv);
+uniform_type!(uvec2, UNSIGNED_INT_VEC2, glProgramUniform2uiv, |v| [v.0, v.1]);
+uniform_type!(uvec3, UNSIGNED_INT_VEC3, glProgramUniform3uiv, |v| [v.0, v.1, v.2]);
+uniform_type!(uvec4, UNSIGNED_INT_VEC4, glProgramUniform4uiv, |v| [v.0, v.1, v.2, v.3]);
+uniform_type!(mat2, FLOAT_MAT2, glProgramUniformMatrix2fv, |&mat2(a, b)| [a.0, a.1, b.0, b.1]);
+uniform_type!(mat3, FLOAT_MAT3, glProgramUniformMatrix3fv, |&mat3(a, b, c)| [a.0, a.1, a.2, b.0, b.1, b.2, c.0, c.1, c.2]);
+uniform_type!(mat4, FLOAT_MAT4, glProgramUniformMatrix4fv, |&mat4(a, b, c, d)| [
+	a.0, a.1, a.2, a.3, b.0, b.1, b.2, b.3, c.0, c.1, c.2, c.3, d.0, d.1, d.2, d.3
+]);
+
+// Samplers and images are set by their (integer) unit.
+impl UniformType for i32 {
+	const GL_TYPE: GLenum = gl::INT;
+
+	fn accepts(typ: GLenum) -> bool {
+		let name = glsl_type_name(typ);
+		typ == gl::INT || name.contains("sampler") || name.contains("image")
+	}
+
+	fn set(program: GLuint, location: i32, values: &[Self]) {
+		glProgramUniform1iv(program, location, values)
+	}
+}
+
+/// Typed handle to a uniform variable of a program, resolved once (see `Program::uniform`).
+/// Setting the uniform does not require the program to be in use.
+///
+/// An inactive uniform (see `Program::optional_uniform`) can be set, but this has no effect.
+#[derive(Clone)]
+pub struct Uniform<T: UniformType> {
+	program: GLuint,
+	location: i32,
+	name: String,
+	array_size: i32,
+	_type: PhantomData<T>,
+}
+
+impl<T> Uniform<T>
+where
+	T: UniformType,
+{
+	/// Resolve uniform `name` of `program`, checking that its type matches `T`.
+	/// Returns `Ok(None)` if the uniform is not active (not declared, or optimized out by the compiler).
+	pub(crate) fn resolve(program: GLuint, name: &str) -> Result<Option<Self>, String> {
+		let info = cached_info(program);
+		let u = match info.uniform(name) {
+			None => return Ok(None),
+			Some(u) => u,
+		};
+		if !T::accepts(u.typ) {
+			return Err(format!(
+				"uniform `{}` has type {}, cannot set it from {}",
+				name,
+				glsl_type_name(u.typ),
+				std::any::type_name::<T>()
+			));
+		}
+		Ok(Some(Self {
+			program,
+			location: u.location,
+			name: name.into(),
+			array_size: u.array_size.max(1),
+			_type: PhantomData,
+		}))
+	}
+
+	/// A handle that ignores all values, for uniforms that are not active.
+	pub(crate) fn inactive(program: GLuint, name: &str) -> Self {
+		Self {
+			program,
+			location: -1,
+			name: name.into(),
+			array_size: 0,
+			_type: PhantomData,
+		}
+	}
+
+	/// Set the uniform's value.
+	/// http://docs.gl/gl4/glProgramUniform
+	pub fn set(&self, value: T) {
+		self.set_array(&[value])
+	}
+
+	/// Set the first `values.len()` elements of a uniform array.
+	/// Panics if there are more values than array elements.
+	/// http://docs.gl/gl4/glProgramUniform
+	pub fn set_array(&self, values: &[T]) {
+		if !self.is_active() {
+			return;
+		}
+		if values.len() > self.array_size as usize {
+			panic!("Uniform::set_array: {} values for `{}`, which has {} element(s)", values.len(), self.name, self.array_size)
+		}
+		registry::check_live("Program", self.program, "Uniform::set");
+		T::set(self.program, self.location, values)
+	}
+
+	/// Whether the uniform is active in the program. Setting an inactive uniform does nothing.
+	pub fn is_active(&self) -> bool {
+		self.location >= 0
+	}
+
+	pub fn location(&self) -> Option<u32> {
+		if self.is_active() {
+			Some(self.location as u32)
+		} else {
+			None
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+}
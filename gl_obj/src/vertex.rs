@@ -0,0 +1,84 @@
+use super::*;
+use gl_safe::*;
+
+/// A Rust type that can be fed to a vertex shader input: `f32`, `i32`, `u32` and their `gl_vec` vectors.
+pub trait AttribType: Sized + Copy + 'static {
+	/// GLSL type of the input, as reported by introspection (e.g. FLOAT_VEC2 for vec2).
+	const GL_TYPE: GLenum;
+
+	/// Number of components, 1 to 4.
+	const COMPONENTS: i32;
+
+	/// Type of each component, e.g. FLOAT.
+	const COMPONENT_TYPE: GLenum;
+}
+
+macro_rules! attrib_type {
+	($t:ty, $gl_type:ident, $components:expr, $component_type:ident) => {
+		impl AttribType for $t {
+			const GL_TYPE: GLenum = gl::$gl_type;
+			const COMPONENTS: i32 = $components;
+			const COMPONENT_TYPE: GLenum = gl::$component_type;
+		}
+	};
+}
+
+attrib_type!(f32, FLOAT, 1, FLOAT);
+attrib_type!(vec2, FLOAT_VEC2, 2, FLOAT);
+attrib_type!(vec3, FLOAT_VEC3, 3, FLOAT);
+attrib_type!(vec4, FLOAT_VEC4, 4, FLOAT);
+attrib_type!(i32, INT, 1, INT);
+attrib_type!(ivec2, INT_VEC2, 2, INT);
+attrib_type!(ivec3, INT_VEC3, 3, INT);
+attrib_type!(ivec4, INT_VEC4, 4, INT);
+attrib_type!(u32, UNSIGNED_INT, 1, UNSIGNED_INT);
+attrib_type!(uvec2, UNSIGNED_INT_VEC2, 2, UNSIGNED_INT);
+attrib_type!(uvec3, UNSIGNED_INT_VEC3, 3, UNSIGNED_INT);
+attrib_type!(uvec4, UNSIGNED_INT_VEC4, 4, UNSIGNED_INT);
+
+/// One field of a `Vertex`.
+#[derive(Clone, Debug)]
+pub struct VertexAttrib {
+	/// Name of the field, and of the vertex shader input it feeds.
+	pub name: &'static str,
+	/// GLSL type, e.g. FLOAT_VEC2.
+	pub typ: GLenum,
+	pub components: i32,
+	pub component_type: GLenum,
+	/// Byte offset in the vertex.
+	pub offset: u32,
+}
+
+impl VertexAttrib {
+	/// Attribute `name` of type `A`, at byte `offset`.
+	pub fn new<A: AttribType>(name: &'static str, offset: usize) -> Self {
+		Self {
+			name,
+			typ: A::GL_TYPE,
+			components: A::COMPONENTS,
+			component_type: A::COMPONENT_TYPE,
+			offset: offset as u32,
+		}
+	}
+
+	/// Whether the attribute is passed to the shader as integers (rather than converted to float).
+	pub fn is_integer(&self) -> bool {
+		self.component_type != gl::FLOAT
+	}
+}
+
+/// An interleaved vertex: a struct whose fields feed the vertex shader inputs of the same name.
+/// Usually derived:
+///
+/// ```ignore
+/// #[derive(Clone, Copy, Vertex)]
+/// struct QuadVertex {
+///     vertex_pos: vec2,
+///     vertex_tex_coord: vec2,
+/// }
+///
+/// let vao = VertexArray::from_layout(&program, &Buffer::new(&vertices, 0))?;
+/// ```
+pub trait Vertex: Sized + Copy + 'static {
+	fn attributes() -> Vec<VertexAttrib>;
+}
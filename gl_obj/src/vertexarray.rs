@@ -2,14 +2,65 @@ use super::*;
 use gl_safe::*;
 extern crate gl;
 
-#[derive(Copy, Clone)]
-pub struct VertexArray(pub GLuint);
+/// A vertex array object. The GL object is deleted when the `VertexArray` is dropped.
+/// The buffers bound with `vertex_buffer` must be kept alive separately.
+pub struct VertexArray(GLuint);
 
 impl VertexArray {
 	/// Create a vertex array object.
 	/// http://docs.gl/gl4/glCreateVertexArrays
+	#[track_caller]
 	pub fn create() -> Self {
-		Self(glCreateVertexArray())
+		let handle = glCreateVertexArray();
+		registry::track("VertexArray", handle);
+		Self(handle)
+	}
+
+	/// A vertex array that feeds the vertex shader inputs of `program` from the interleaved vertices in `buffer`,
+	/// each field to the input of the same name (see `Vertex`).
+	/// Fails if a field has no active input of the same type, or if an input is not fed by any field.
+	#[track_caller]
+	pub fn from_layout<T: Vertex>(program: &Program, buffer: &Buffer<T>) -> Result<Self, String> {
+		let info = cached_info(program.handle());
+		let attribs = T::attributes();
+		let type_name = std::any::type_name::<T>();
+
+		let mut errors = Vec::new();
+		for input in &info.attributes {
+			if !attribs.iter().any(|a| a.name == input.name) {
+				errors.push(format!("input `{}` has no field in {}", input.name, type_name));
+			}
+		}
+		let mut vao = Self::create();
+		for a in &attribs {
+			let input = match info.attributes.iter().find(|i| i.name == a.name) {
+				Some(input) => input,
+				None => {
+					errors.push(format!("field `{}` of {} has no active input (not declared or optimized out)", a.name, type_name));
+					continue;
+				}
+			};
+			if input.typ != a.typ {
+				errors.push(format!("input `{}` has type {}, field has type {}", a.name, glsl_type_name(input.typ), glsl_type_name(a.typ)));
+				continue;
+			}
+			let index = input.location;
+			vao = vao.enable_attrib(index);
+			vao = if a.is_integer() {
+				vao.attrib_iformat(index, a.components, a.component_type, a.offset)
+			} else {
+				vao.attrib_format(index, a.components, a.component_type, false, a.offset)
+			};
+			vao = vao.attrib_binding(index, 0);
+		}
+		if !errors.is_empty() {
+			return Err(format!("vertex layout: {}", errors.join(", ")));
+		}
+		Ok(vao.vertex_buffer(0, buffer, 0, std::mem::size_of::<T>() as GLsizei))
+	}
+
+	pub fn handle(&self) -> GLuint {
+		self.0
 	}
 
 	/// Enable a generic vertex attribute array.
@@ -49,24 +100,37 @@ impl VertexArray {
 
 	/// Bind a buffer to a vertex buffer bind point.
 	/// https://khronos.org/registry/OpenGL-Refpages/gl4/html/glBindVertexBuffer.xhtml
-	pub fn vertex_buffer<T>(self, bindingindex: GLuint, buffer: Buffer<T>, offset: GLintptr, stride: GLsizei) -> Self
+	pub fn vertex_buffer<T>(self, bindingindex: GLuint, buffer: &Buffer<T>, offset: GLintptr, stride: GLsizei) -> Self
 	where
 		T: Sized + Copy + 'static,
 	{
-		glVertexArrayVertexBuffer(self.0, bindingindex, buffer.into(), offset, stride);
+		glVertexArrayVertexBuffer(self.0, bindingindex, buffer.handle(), offset, stride);
 		self
 	}
 
 	/// Bind a buffer to a vertex buffer bind point.
 	/// https://khronos.org/registry/OpenGL-Refpages/gl4/html/glBindVertexBuffer.xhtml
 	#[allow(non_snake_case)]
-	pub fn bind(self) {
+	pub fn bind(&self) {
 		glBindVertexArray(self.0)
 	}
+
+	/// Bind the vertex array and draw with the parameters in `commands[index]`,
+	/// e.g. as produced by a compute pass (see `IndirectArgs`). The program must be in use.
+	/// http://docs.gl/gl4/glDrawArraysIndirect
+	pub fn draw_arrays_indirect(&self, mode: GLenum, commands: &Buffer<DrawArraysIndirectCommand>, index: u32) {
+		if index >= commands.len() {
+			panic!("VertexArray::draw_arrays_indirect: command {} out of bounds (len {})", index, commands.len())
+		}
+		self.bind();
+		glBindBuffer(gl::DRAW_INDIRECT_BUFFER, commands.handle());
+		glDrawArraysIndirect(mode, index as usize * std::mem::size_of::<DrawArraysIndirectCommand>());
+	}
 }
 
-impl Into<GLuint> for VertexArray {
-	fn into(self) -> GLuint {
-		self.0
+impl Drop for VertexArray {
+	fn drop(&mut self) {
+		registry::untrack("VertexArray", self.0);
+		glDeleteVertexArray(self.0)
 	}
 }
@@ -25,6 +25,10 @@ fn format_num_el(format: GLenum) -> usize {
 		gl::RG => 2,
 		gl::RGB => 3,
 		gl::RGBA => 4,
+		gl::RED_INTEGER => 1,
+		gl::RG_INTEGER => 2,
+		gl::RGB_INTEGER => 3,
+		gl::RGBA_INTEGER => 4,
 		x => panic!("unknown pixel format: {}", x),
 	}
 }
@@ -32,6 +36,12 @@ fn format_num_el(format: GLenum) -> usize {
 fn type_num_bytes(typ: GLenum) -> usize {
 	match typ {
 		gl::FLOAT => 4,
+		gl::HALF_FLOAT => 2,
+		gl::INT => 4,
+		gl::UNSIGNED_INT => 4,
+		gl::SHORT => 2,
+		gl::UNSIGNED_SHORT => 2,
+		gl::BYTE => 1,
 		gl::UNSIGNED_BYTE => 1,
 		x => panic!("unknown pixel type: {}", x),
 	}
@@ -1,6 +1,8 @@
 use super::check;
 use super::*;
 use std::mem;
+use std::mem::MaybeUninit;
+use std::ptr;
 
 /// Create a buffer object.
 /// http://docs.gl/gl4/glCreateBuffers
@@ -24,6 +26,14 @@ where
 	check::gl_error();
 }
 
+/// Creates a buffer object's immutable data store of `bytes` bytes, with undefined contents.
+/// http://docs.gl/gl4/glBufferStorage
+#[allow(non_snake_case)]
+pub fn glNamedBufferStorageSize(buffer: GLuint, bytes: usize, flags: GLbitfield) {
+	unsafe { gl::NamedBufferStorage(buffer, bytes as isize, ptr::null(), flags) }
+	check::gl_error();
+}
+
 /// Returns a subset of a buffer object's data store.
 /// http://docs.gl/gl4/glGetBufferSubData
 #[allow(non_snake_case)]
@@ -37,6 +47,84 @@ where
 	check::gl_error()
 }
 
+/// Like `glGetNamedBufferSubData`, but fills possibly uninitialized memory (e.g. `Vec::spare_capacity_mut`).
+/// http://docs.gl/gl4/glGetBufferSubData
+#[allow(non_snake_case)]
+pub fn glGetNamedBufferSubDataUninit<T>(buffer: GLuint, offset: usize, data: &mut [MaybeUninit<T>])
+where
+	T: Sized + Copy + 'static,
+{
+	let bytes = mem::size_of_val(data);
+	unsafe { gl::GetNamedBufferSubData(buffer, offset as isize, bytes as isize, data.as_mut_ptr() as *mut _) };
+	check::gl_error()
+}
+
+/// Updates a subset of a buffer object's data store, starting at byte `offset`.
+/// The buffer must have been created with DYNAMIC_STORAGE_BIT.
+/// http://docs.gl/gl4/glBufferSubData
+#[allow(non_snake_case)]
+pub fn glNamedBufferSubData<T>(buffer: GLuint, offset: usize, data: &[T])
+where
+	T: Sized + Copy + 'static,
+{
+	let bytes = mem::size_of_val(data);
+	unsafe { gl::NamedBufferSubData(buffer, offset as isize, bytes as isize, data.as_ptr() as *const _) };
+	check::gl_error()
+}
+
+/// Fill a buffer object's data store with a fixed value.
+/// `format` and `typ` describe `data`, which is converted to `internalformat` and replicated.
+/// http://docs.gl/gl4/glClearBufferData
+#[allow(non_snake_case)]
+pub fn glClearNamedBufferData<T>(buffer: GLuint, internalformat: GLenum, format: GLenum, typ: GLenum, data: &T)
+where
+	T: Sized + Copy + 'static,
+{
+	unsafe { gl::ClearNamedBufferData(buffer, internalformat, format, typ, data as *const T as *const _) };
+	check::gl_error()
+}
+
+/// Map all or part of a buffer object's data store into the client's address space.
+/// Offset and length are in bytes.
+/// http://docs.gl/gl4/glMapBufferRange
+#[allow(non_snake_case)]
+pub fn glMapNamedBufferRange(buffer: GLuint, offset: usize, length: usize, access: GLbitfield) -> *mut std::os::raw::c_void {
+	let ptr = unsafe { gl::MapNamedBufferRange(buffer, offset as isize, length as isize, access) };
+	check::gl_error();
+	if ptr.is_null() {
+		panic!("glMapNamedBufferRange: buffer {} could not be mapped", buffer)
+	}
+	ptr
+}
+
+/// Indicate modifications to a range of a mapped buffer (mapped with MAP_FLUSH_EXPLICIT_BIT).
+/// Offset (relative to the mapped range) and length are in bytes.
+/// http://docs.gl/gl4/glFlushMappedBufferRange
+#[allow(non_snake_case)]
+pub fn glFlushMappedNamedBufferRange(buffer: GLuint, offset: usize, length: usize) {
+	unsafe { gl::FlushMappedNamedBufferRange(buffer, offset as isize, length as isize) };
+	check::gl_error()
+}
+
+/// Release the mapping of a buffer object's data store into the client's address space.
+/// Returns false if the data store contents have become corrupt during mapping.
+/// http://docs.gl/gl4/glUnmapBuffer
+#[allow(non_snake_case)]
+pub fn glUnmapNamedBuffer(buffer: GLuint) -> bool {
+	let ok = unsafe { gl::UnmapNamedBuffer(buffer) };
+	check::gl_error();
+	ok == gl::TRUE
+}
+
+/// Copy all or part of the data store of a buffer object to the data store of another buffer object.
+/// Offsets and size are in bytes.
+/// http://docs.gl/gl4/glCopyBufferSubData
+#[allow(non_snake_case)]
+pub fn glCopyNamedBufferSubData(read_buffer: GLuint, write_buffer: GLuint, read_offset: usize, write_offset: usize, size: usize) {
+	unsafe { gl::CopyNamedBufferSubData(read_buffer, write_buffer, read_offset as isize, write_offset as isize, size as isize) };
+	check::gl_error()
+}
+
 /// Delete a buffer object.
 /// http://docs.gl/gl4/glDeleteBuffers
 #[allow(non_snake_case)]
@@ -53,6 +141,14 @@ pub fn glBindBufferBase(target: GLenum, index: GLuint, buffer: GLuint) {
 	check::gl_error()
 }
 
+/// Bind a range of a buffer object (`size` bytes starting at byte `offset`) to an indexed buffer target.
+/// http://docs.gl/gl4/glBindBufferRange
+#[allow(non_snake_case)]
+pub fn glBindBufferRange(target: GLenum, index: GLuint, buffer: GLuint, offset: usize, size: usize) {
+	unsafe { gl::BindBufferRange(target, index, buffer, offset as GLintptr, size as GLsizeiptr) };
+	check::gl_error()
+}
+
 /*
 /// Creates and initializes a buffer object's data store.
 /// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glBufferData.xhtml
@@ -0,0 +1,126 @@
+use super::check;
+use super::*;
+use std::mem;
+
+/// Create a framebuffer object.
+/// http://docs.gl/gl4/glCreateFramebuffers
+#[allow(non_snake_case)]
+pub fn glCreateFramebuffer() -> GLuint {
+	let mut result = 0;
+	unsafe { gl::CreateFramebuffers(1, &mut result) };
+	check::gl_error();
+	result
+}
+
+/// Delete a framebuffer object.
+/// http://docs.gl/gl4/glDeleteFramebuffers
+#[allow(non_snake_case)]
+pub fn glDeleteFramebuffer(framebuffer: GLuint) {
+	unsafe { gl::DeleteFramebuffers(1, &framebuffer) };
+	check::gl_error()
+}
+
+/// Bind a framebuffer to a framebuffer target (DRAW_FRAMEBUFFER, READ_FRAMEBUFFER or FRAMEBUFFER).
+/// http://docs.gl/gl4/glBindFramebuffer
+#[allow(non_snake_case)]
+pub fn glBindFramebuffer(target: GLenum, framebuffer: GLuint) {
+	unsafe { gl::BindFramebuffer(target, framebuffer) };
+	check::gl_error()
+}
+
+/// Attach a level of a texture object as a logical buffer of a framebuffer object.
+/// http://docs.gl/gl4/glFramebufferTexture
+#[allow(non_snake_case)]
+pub fn glNamedFramebufferTexture(framebuffer: GLuint, attachment: GLenum, texture: GLuint, level: i32) {
+	unsafe { gl::NamedFramebufferTexture(framebuffer, attachment, texture, level) };
+	check::gl_error()
+}
+
+/// Attach a renderbuffer as a logical buffer of a framebuffer object.
+/// http://docs.gl/gl4/glFramebufferRenderbuffer
+#[allow(non_snake_case)]
+pub fn glNamedFramebufferRenderbuffer(framebuffer: GLuint, attachment: GLenum, renderbuffer: GLuint) {
+	unsafe { gl::NamedFramebufferRenderbuffer(framebuffer, attachment, gl::RENDERBUFFER, renderbuffer) };
+	check::gl_error()
+}
+
+/// Specifies a list of color buffers to be drawn into.
+/// http://docs.gl/gl4/glDrawBuffers
+#[allow(non_snake_case)]
+pub fn glNamedFramebufferDrawBuffers(framebuffer: GLuint, bufs: &[GLenum]) {
+	unsafe { gl::NamedFramebufferDrawBuffers(framebuffer, bufs.len() as i32, bufs.as_ptr()) };
+	check::gl_error()
+}
+
+/// Select a color buffer source for pixels.
+/// http://docs.gl/gl4/glReadBuffer
+#[allow(non_snake_case)]
+pub fn glNamedFramebufferReadBuffer(framebuffer: GLuint, mode: GLenum) {
+	unsafe { gl::NamedFramebufferReadBuffer(framebuffer, mode) };
+	check::gl_error()
+}
+
+/// Check the completeness status of a framebuffer.
+/// Returns FRAMEBUFFER_COMPLETE on success.
+/// http://docs.gl/gl4/glCheckFramebufferStatus
+#[allow(non_snake_case)]
+pub fn glCheckNamedFramebufferStatus(framebuffer: GLuint, target: GLenum) -> GLenum {
+	let result = unsafe { gl::CheckNamedFramebufferStatus(framebuffer, target) };
+	check::gl_error();
+	result
+}
+
+/// Clear individual buffers of a framebuffer.
+/// http://docs.gl/gl4/glClearBuffer
+#[allow(non_snake_case)]
+pub fn glClearNamedFramebufferfv(framebuffer: GLuint, buffer: GLenum, drawbuffer: i32, value: &[f32]) {
+	unsafe { gl::ClearNamedFramebufferfv(framebuffer, buffer, drawbuffer, value.as_ptr()) };
+	check::gl_error()
+}
+
+/// Copy a block of pixels from one framebuffer object to another.
+/// http://docs.gl/gl4/glBlitFramebuffer
+#[allow(non_snake_case)]
+pub fn glBlitNamedFramebuffer(read: GLuint, draw: GLuint, src: [i32; 4], dst: [i32; 4], mask: GLbitfield, filter: GLenum) {
+	unsafe { gl::BlitNamedFramebuffer(read, draw, src[0], src[1], src[2], src[3], dst[0], dst[1], dst[2], dst[3], mask, filter) };
+	check::gl_error()
+}
+
+/// Read a block of pixels from the framebuffer bound to READ_FRAMEBUFFER.
+/// http://docs.gl/gl4/glReadPixels
+#[allow(non_snake_case)]
+pub fn glReadnPixels<T>(x: i32, y: i32, width: i32, height: i32, format: GLenum, typ: GLenum, pixels: &mut [T])
+where
+	T: Sized + Copy + 'static,
+{
+	check::image_size(&[width, height], format, typ, pixels);
+	let bytes = mem::size_of_val(pixels);
+	unsafe { gl::ReadnPixels(x, y, width, height, format, typ, bytes as i32, pixels.as_mut_ptr() as *mut _) };
+	check::gl_error()
+}
+
+/// Create a renderbuffer object.
+/// http://docs.gl/gl4/glCreateRenderbuffers
+#[allow(non_snake_case)]
+pub fn glCreateRenderbuffer() -> GLuint {
+	let mut result = 0;
+	unsafe { gl::CreateRenderbuffers(1, &mut result) };
+	check::gl_error();
+	result
+}
+
+/// Establish data storage, format and dimensions of a renderbuffer object's image.
+/// http://docs.gl/gl4/glRenderbufferStorage
+#[allow(non_snake_case)]
+pub fn glNamedRenderbufferStorage(renderbuffer: GLuint, internalformat: GLenum, width: i32, height: i32) {
+	unsafe { gl::NamedRenderbufferStorage(renderbuffer, internalformat, width, height) };
+	check::gl_error()
+}
+
+/// Delete a renderbuffer object.
+/// http://docs.gl/gl4/glDeleteRenderbuffers
+#[allow(non_snake_case)]
+pub fn glDeleteRenderbuffer(renderbuffer: GLuint) {
+	unsafe { gl::DeleteRenderbuffers(1, &renderbuffer) };
+	check::gl_error()
+}
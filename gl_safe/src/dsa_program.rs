@@ -82,6 +82,14 @@ pub fn glShaderStorageBlockBinding(program: GLuint, storageBlockIndex: GLuint, s
 	check::gl_error()
 }
 
+/// Assign a binding point to an active uniform block.
+/// http://docs.gl/gl4/glUniformBlockBinding
+#[allow(non_snake_case)]
+pub fn glUniformBlockBinding(program: GLuint, uniformBlockIndex: GLuint, uniformBlockBinding: GLuint) {
+	unsafe { gl::UniformBlockBinding(program, uniformBlockIndex, uniformBlockBinding) };
+	check::gl_error()
+}
+
 /// Query the index of a named resource within a program.
 /// http://docs.gl/gl4/glGetProgramResourceIndex
 #[allow(non_snake_case)]
@@ -94,6 +102,109 @@ pub fn glGetProgramResourceIndex(program: GLuint, programInterface: GLenum, name
 	result
 }
 
+/// Query a property of an interface in a program (e.g. ACTIVE_RESOURCES of UNIFORM).
+/// http://docs.gl/gl4/glGetProgramInterface
+#[allow(non_snake_case)]
+pub fn glGetProgramInterfaceiv(program: GLuint, programInterface: GLenum, pname: GLenum) -> i32 {
+	let mut result = 0;
+	unsafe { gl::GetProgramInterfaceiv(program, programInterface, pname, &mut result) };
+	check::gl_error();
+	result
+}
+
+/// Retrieve values for multiple properties of a single active resource within a program object.
+/// Returns one value per property (properties returning arrays, like ACTIVE_VARIABLES, are not supported).
+/// http://docs.gl/gl4/glGetProgramResource
+#[allow(non_snake_case)]
+pub fn glGetProgramResourceiv(program: GLuint, programInterface: GLenum, index: GLuint, props: &[GLenum]) -> Vec<i32> {
+	let mut result = vec![0; props.len()];
+	let mut length = 0;
+	unsafe {
+		gl::GetProgramResourceiv(
+			program,
+			programInterface,
+			index,
+			props.len() as i32,
+			props.as_ptr(),
+			result.len() as i32,
+			&mut length,
+			result.as_mut_ptr(),
+		)
+	};
+	check::gl_error();
+	result.truncate(length as usize);
+	result
+}
+
+/// Query the name of an indexed resource within a program.
+/// http://docs.gl/gl4/glGetProgramResourceName
+#[allow(non_snake_case)]
+pub fn glGetProgramResourceName(program: GLuint, programInterface: GLenum, index: GLuint) -> String {
+	let max_length = glGetProgramResourceiv(program, programInterface, index, &[gl::NAME_LENGTH])[0];
+	if max_length == 0 {
+		return "".into();
+	}
+	let mut buf = vec![0u8; max_length as usize];
+	let mut length = 0;
+	unsafe { gl::GetProgramResourceName(program, programInterface, index, max_length, &mut length, buf.as_mut_ptr() as *mut GLchar) };
+	check::gl_error();
+	buf.truncate(length as usize); // skip nul terminator
+	String::from_utf8(buf).expect("invalid utf-8")
+}
+
+/// Returns the value of a uniform variable (n elements, e.g. 2 for a vec2).
+/// http://docs.gl/gl4/glGetUniform
+#[allow(non_snake_case)]
+pub fn glGetnUniformfv(program: GLuint, location: i32, n: usize) -> Vec<f32> {
+	let mut result = vec![0.0; n];
+	unsafe { gl::GetnUniformfv(program, location, (n * 4) as i32, result.as_mut_ptr()) };
+	check::gl_error();
+	result
+}
+
+/// Returns the value of a uniform variable (n elements, e.g. 2 for an ivec2).
+/// http://docs.gl/gl4/glGetUniform
+#[allow(non_snake_case)]
+pub fn glGetnUniformiv(program: GLuint, location: i32, n: usize) -> Vec<i32> {
+	let mut result = vec![0; n];
+	unsafe { gl::GetnUniformiv(program, location, (n * 4) as i32, result.as_mut_ptr()) };
+	check::gl_error();
+	result
+}
+
+/// Returns the value of a uniform variable (n elements, e.g. 2 for a uvec2).
+/// http://docs.gl/gl4/glGetUniform
+#[allow(non_snake_case)]
+pub fn glGetnUniformuiv(program: GLuint, location: i32, n: usize) -> Vec<u32> {
+	let mut result = vec![0; n];
+	unsafe { gl::GetnUniformuiv(program, location, (n * 4) as i32, result.as_mut_ptr()) };
+	check::gl_error();
+	result
+}
+
+/// Returns the value of a uniform variable (n elements, e.g. 2 for a dvec2).
+/// http://docs.gl/gl4/glGetUniform
+#[allow(non_snake_case)]
+pub fn glGetnUniformdv(program: GLuint, location: i32, n: usize) -> Vec<f64> {
+	let mut result = vec![0.0; n];
+	unsafe { gl::GetnUniformdv(program, location, (n * 8) as i32, result.as_mut_ptr()) };
+	check::gl_error();
+	result
+}
+
+/// Returns the handles of the shader objects attached to a program object.
+/// http://docs.gl/gl4/glGetAttachedShaders
+#[allow(non_snake_case)]
+pub fn glGetAttachedShaders(program: GLuint) -> Vec<GLuint> {
+	let max_count = glGetProgramiv(program, gl::ATTACHED_SHADERS, 1)[0];
+	let mut result = vec![0; max_count as usize];
+	let mut count = 0;
+	unsafe { gl::GetAttachedShaders(program, max_count, &mut count, result.as_mut_ptr()) };
+	check::gl_error();
+	result.truncate(count as usize);
+	result
+}
+
 /// Specify the value of a uniform variable for a specified program object.
 /// http://docs.gl/gl4/glProgramUniform
 #[allow(non_snake_case)]
@@ -158,6 +269,126 @@ pub fn glProgramUniform1i(program: GLuint, location: i32, v0: i32) {
 	check::gl_error();
 }
 
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform1fv(program: GLuint, location: i32, value: &[f32]) {
+	unsafe { gl::ProgramUniform1fv(program, location, value.len() as i32, value.as_ptr()) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform2fv(program: GLuint, location: i32, value: &[[f32; 2]]) {
+	unsafe { gl::ProgramUniform2fv(program, location, value.len() as i32, value.as_ptr() as *const f32) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform3fv(program: GLuint, location: i32, value: &[[f32; 3]]) {
+	unsafe { gl::ProgramUniform3fv(program, location, value.len() as i32, value.as_ptr() as *const f32) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform4fv(program: GLuint, location: i32, value: &[[f32; 4]]) {
+	unsafe { gl::ProgramUniform4fv(program, location, value.len() as i32, value.as_ptr() as *const f32) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform1iv(program: GLuint, location: i32, value: &[i32]) {
+	unsafe { gl::ProgramUniform1iv(program, location, value.len() as i32, value.as_ptr()) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform2iv(program: GLuint, location: i32, value: &[[i32; 2]]) {
+	unsafe { gl::ProgramUniform2iv(program, location, value.len() as i32, value.as_ptr() as *const i32) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform3iv(program: GLuint, location: i32, value: &[[i32; 3]]) {
+	unsafe { gl::ProgramUniform3iv(program, location, value.len() as i32, value.as_ptr() as *const i32) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform4iv(program: GLuint, location: i32, value: &[[i32; 4]]) {
+	unsafe { gl::ProgramUniform4iv(program, location, value.len() as i32, value.as_ptr() as *const i32) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform1uiv(program: GLuint, location: i32, value: &[u32]) {
+	unsafe { gl::ProgramUniform1uiv(program, location, value.len() as i32, value.as_ptr()) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform2uiv(program: GLuint, location: i32, value: &[[u32; 2]]) {
+	unsafe { gl::ProgramUniform2uiv(program, location, value.len() as i32, value.as_ptr() as *const u32) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform3uiv(program: GLuint, location: i32, value: &[[u32; 3]]) {
+	unsafe { gl::ProgramUniform3uiv(program, location, value.len() as i32, value.as_ptr() as *const u32) };
+	check::gl_error();
+}
+
+/// Specify the value of a uniform variable (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniform4uiv(program: GLuint, location: i32, value: &[[u32; 4]]) {
+	unsafe { gl::ProgramUniform4uiv(program, location, value.len() as i32, value.as_ptr() as *const u32) };
+	check::gl_error();
+}
+
+/// Specify the value of a column-major 2x2 matrix uniform (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniformMatrix2fv(program: GLuint, location: i32, value: &[[f32; 4]]) {
+	unsafe { gl::ProgramUniformMatrix2fv(program, location, value.len() as i32, gl::FALSE, value.as_ptr() as *const f32) };
+	check::gl_error();
+}
+
+/// Specify the value of a column-major 3x3 matrix uniform (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniformMatrix3fv(program: GLuint, location: i32, value: &[[f32; 9]]) {
+	unsafe { gl::ProgramUniformMatrix3fv(program, location, value.len() as i32, gl::FALSE, value.as_ptr() as *const f32) };
+	check::gl_error();
+}
+
+/// Specify the value of a column-major 4x4 matrix uniform (or array) for a specified program object.
+/// http://docs.gl/gl4/glProgramUniform
+#[allow(non_snake_case)]
+pub fn glProgramUniformMatrix4fv(program: GLuint, location: i32, value: &[[f32; 16]]) {
+	unsafe { gl::ProgramUniformMatrix4fv(program, location, value.len() as i32, gl::FALSE, value.as_ptr() as *const f32) };
+	check::gl_error();
+}
+
 /// Installs a program object as part of current rendering state.
 /// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glUseProgram.xhtml
 #[allow(non_snake_case)]
@@ -165,3 +396,12 @@ pub fn glUseProgram(program: GLuint) {
 	unsafe { gl::UseProgram(program) };
 	check::gl_error();
 }
+
+/// Deletes a program object.
+/// Deletion is deferred while the program is in use.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glDeleteProgram.xhtml
+#[allow(non_snake_case)]
+pub fn glDeleteProgram(program: GLuint) {
+	unsafe { gl::DeleteProgram(program) };
+	check::gl_error();
+}
@@ -0,0 +1,64 @@
+use super::check;
+use super::*;
+
+/// Create a query object.
+/// http://docs.gl/gl4/glCreateQueries
+#[allow(non_snake_case)]
+pub fn glCreateQuery(target: GLenum) -> GLuint {
+	let mut result = 0;
+	unsafe { gl::CreateQueries(target, 1, &mut result) };
+	check::gl_error();
+	result
+}
+
+/// Delete a query object.
+/// http://docs.gl/gl4/glDeleteQueries
+#[allow(non_snake_case)]
+pub fn glDeleteQuery(id: GLuint) {
+	unsafe { gl::DeleteQueries(1, &id) };
+	check::gl_error()
+}
+
+/// Delimit the boundaries of a query object.
+/// http://docs.gl/gl4/glBeginQuery
+#[allow(non_snake_case)]
+pub fn glBeginQuery(target: GLenum, id: GLuint) {
+	unsafe { gl::BeginQuery(target, id) };
+	check::gl_error()
+}
+
+/// Delimit the boundaries of a query object.
+/// http://docs.gl/gl4/glBeginQuery
+#[allow(non_snake_case)]
+pub fn glEndQuery(target: GLenum) {
+	unsafe { gl::EndQuery(target) };
+	check::gl_error()
+}
+
+/// Record the GL time into a query object after all previous commands have reached the GL server but have not yet necessarily executed.
+/// http://docs.gl/gl4/glQueryCounter
+#[allow(non_snake_case)]
+pub fn glQueryCounter(id: GLuint, target: GLenum) {
+	unsafe { gl::QueryCounter(id, target) };
+	check::gl_error()
+}
+
+/// Return parameters of a query object.
+/// http://docs.gl/gl4/glGetQueryObject
+#[allow(non_snake_case)]
+pub fn glGetQueryObjectiv(id: GLuint, pname: GLenum) -> i32 {
+	let mut result = 0;
+	unsafe { gl::GetQueryObjectiv(id, pname, &mut result) };
+	check::gl_error();
+	result
+}
+
+/// Return parameters of a query object.
+/// http://docs.gl/gl4/glGetQueryObject
+#[allow(non_snake_case)]
+pub fn glGetQueryObjectui64v(id: GLuint, pname: GLenum) -> u64 {
+	let mut result = 0;
+	unsafe { gl::GetQueryObjectui64v(id, pname, &mut result) };
+	check::gl_error();
+	result
+}
@@ -59,3 +59,28 @@ pub fn glGetShaderInfoLog(shader: GLuint) -> String {
 	};
 	String::from_utf8(buf).expect("invalid utf-8")
 }
+
+/// Returns the source code string from a shader object.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetShaderSource.xhtml
+#[allow(non_snake_case)]
+pub fn glGetShaderSource(shader: GLuint) -> String {
+	let max_length = glGetShaderiv(shader, gl::SHADER_SOURCE_LENGTH);
+	if max_length == 0 {
+		return "".into();
+	}
+	let mut buf = vec![0u8; max_length as usize];
+	let mut length = 0;
+	unsafe { gl::GetShaderSource(shader, max_length, &mut length, buf.as_mut_ptr() as *mut GLchar) };
+	check::gl_error();
+	buf.truncate(length as usize); // skip nul terminator
+	String::from_utf8(buf).expect("invalid utf-8")
+}
+
+/// Deletes a shader object.
+/// Deletion is deferred while the shader is attached to a program.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glDeleteShader.xhtml
+#[allow(non_snake_case)]
+pub fn glDeleteShader(shader: GLuint) {
+	unsafe { gl::DeleteShader(shader) };
+	check::gl_error();
+}
@@ -72,6 +72,29 @@ where
 	check::gl_error()
 }
 
+/// Return a texture image.
+/// http://docs.gl/gl4/glGetTexImage
+#[allow(non_snake_case)]
+pub fn glGetTextureImage<T>(texture: GLuint, level: i32, size: &[i32], format: GLenum, typ: GLenum, pixels: &mut [T])
+where
+	T: Sized + Copy + 'static,
+{
+	check::image_size(size, format, typ, pixels);
+	let bytes = mem::size_of_val(pixels);
+	unsafe { gl::GetTextureImage(texture, level, format, typ, bytes as i32, pixels.as_mut_ptr() as *mut _) };
+	check::gl_error()
+}
+
+/// Return a texture image into the buffer currently bound to GL_PIXEL_PACK_BUFFER,
+/// starting at byte `offset`. `bytes` is the space available in the buffer.
+/// Does not wait for the GPU to finish.
+/// http://docs.gl/gl4/glGetTexImage
+#[allow(non_snake_case)]
+pub fn glGetTextureImageOffset(texture: GLuint, level: i32, format: GLenum, typ: GLenum, bytes: usize, offset: usize) {
+	unsafe { gl::GetTextureImage(texture, level, format, typ, bytes as i32, offset as *mut _) };
+	check::gl_error()
+}
+
 /// Set texture parameters.
 /// http://docs.gl/gl4/glTexParameter
 #[allow(non_snake_case)]
@@ -114,3 +137,11 @@ pub fn glBindTextureUnit(unit: GLuint, texture: GLuint) {
 	unsafe { gl::BindTextureUnit(unit, texture) };
 	check::gl_error()
 }
+
+/// Delete a texture object.
+/// http://docs.gl/gl4/glDeleteTextures
+#[allow(non_snake_case)]
+pub fn glDeleteTexture(texture: GLuint) {
+	unsafe { gl::DeleteTextures(1, &texture) };
+	check::gl_error()
+}
@@ -68,3 +68,11 @@ pub fn glVertexArrayVertexBuffer(vaobj: GLuint, bindingindex: GLuint, buffer: GL
 	unsafe { gl::VertexArrayVertexBuffer(vaobj, bindingindex, buffer, offset, stride) };
 	check::gl_error();
 }
+
+/// Delete a vertex array object.
+/// http://docs.gl/gl4/glDeleteVertexArrays
+#[allow(non_snake_case)]
+pub fn glDeleteVertexArray(vaobj: GLuint) {
+	unsafe { gl::DeleteVertexArrays(1, &vaobj) };
+	check::gl_error();
+}
@@ -1,17 +1,23 @@
 mod check;
 mod dsa_buffer;
+mod dsa_framebuffer;
 mod dsa_program;
+mod dsa_query;
 mod dsa_shader;
 mod dsa_texture;
 mod dsa_vertexarray;
 mod glenum;
 mod safewrappers;
+mod sync;
 
 pub use dsa_buffer::*;
+pub use dsa_framebuffer::*;
 pub use dsa_program::*;
+pub use dsa_query::*;
 pub use dsa_shader::*;
 pub use dsa_texture::*;
 pub use dsa_vertexarray::*;
 pub use safewrappers::*;
+pub use sync::*;
 
 pub use gl::types::*;
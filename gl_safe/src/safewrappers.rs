@@ -76,6 +76,24 @@ pub fn glDispatchCompute(num_groups_x: u32, num_groups_y: u32, num_groups_z: u32
 	check::gl_error()
 }
 
+/// Launch compute work groups, with the number of groups read from the buffer bound to DISPATCH_INDIRECT_BUFFER
+/// at byte offset `indirect`.
+/// http://docs.gl/gl4/glDispatchComputeIndirect
+#[allow(non_snake_case)]
+pub fn glDispatchComputeIndirect(indirect: usize) {
+	unsafe { gl::DispatchComputeIndirect(indirect as GLintptr) };
+	check::gl_error()
+}
+
+/// Render primitives from array data, with the parameters read from the buffer bound to DRAW_INDIRECT_BUFFER
+/// at byte offset `indirect`.
+/// http://docs.gl/gl4/glDrawArraysIndirect
+#[allow(non_snake_case)]
+pub fn glDrawArraysIndirect(mode: GLenum, indirect: usize) {
+	unsafe { gl::DrawArraysIndirect(mode, indirect as *const _) };
+	check::gl_error()
+}
+
 /// Render primitives from array data.
 /// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glDrawArrays.xhtml
 #[allow(non_snake_case)]
@@ -100,6 +118,30 @@ pub fn glClear(mask: GLbitfield) {
 	check::gl_error()
 }
 
+/// Enable server-side GL capabilities, e.g. PROGRAM_POINT_SIZE.
+/// http://docs.gl/gl4/glEnable
+#[allow(non_snake_case)]
+pub fn glEnable(cap: GLenum) {
+	unsafe { gl::Enable(cap) };
+	check::gl_error()
+}
+
+/// Disable server-side GL capabilities.
+/// http://docs.gl/gl4/glEnable
+#[allow(non_snake_case)]
+pub fn glDisable(cap: GLenum) {
+	unsafe { gl::Disable(cap) };
+	check::gl_error()
+}
+
+/// Set the viewport.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glViewport.xhtml
+#[allow(non_snake_case)]
+pub fn glViewport(x: i32, y: i32, width: i32, height: i32) {
+	unsafe { gl::Viewport(x, y, width, height) };
+	check::gl_error()
+}
+
 /// Returns a parameter from a program object.
 /// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGetProgram.xhtml.
 /// TODO: could be more than 1 !!!
@@ -120,6 +162,16 @@ pub fn glGetProgramiv(program: GLuint, pname: GLenum, n: usize) -> Vec<i32> {
 	result
 }
 
+/// Return the value of an indexed state variable, e.g. IMAGE_BINDING_NAME of an image unit.
+/// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGet.xhtml
+#[allow(non_snake_case)]
+pub fn glGetIntegeri_v(target: GLenum, index: GLuint) -> i32 {
+	let mut result = 0;
+	unsafe { gl::GetIntegeri_v(target, index, &mut result) };
+	check::gl_error();
+	result
+}
+
 /// Generate a (single) vertex array object name.
 /// https://www.khronos.org/registry/OpenGL-Refpages/gl4/html/glGenVertexArrays.xhtml
 #[allow(non_snake_case)]
@@ -0,0 +1,37 @@
+use super::check;
+use super::*;
+
+/// Create a new sync object and insert it into the GL command stream.
+/// http://docs.gl/gl4/glFenceSync
+#[allow(non_snake_case)]
+pub fn glFenceSync(condition: GLenum, flags: GLbitfield) -> GLsync {
+	let sync = unsafe { gl::FenceSync(condition, flags) };
+	check::gl_error();
+	sync
+}
+
+/// Block and wait for a sync object to become signaled, for at most `timeout` nanoseconds.
+/// Returns ALREADY_SIGNALED, TIMEOUT_EXPIRED, CONDITION_SATISFIED or WAIT_FAILED.
+/// http://docs.gl/gl4/glClientWaitSync
+#[allow(non_snake_case, clippy::not_unsafe_ptr_arg_deref)]
+pub fn glClientWaitSync(sync: GLsync, flags: GLbitfield, timeout: u64) -> GLenum {
+	let result = unsafe { gl::ClientWaitSync(sync, flags, timeout) };
+	check::gl_error();
+	result
+}
+
+/// Instruct the GL server to block until the specified sync object becomes signaled.
+/// http://docs.gl/gl4/glWaitSync
+#[allow(non_snake_case, clippy::not_unsafe_ptr_arg_deref)]
+pub fn glWaitSync(sync: GLsync) {
+	unsafe { gl::WaitSync(sync, 0, gl::TIMEOUT_IGNORED) };
+	check::gl_error()
+}
+
+/// Delete a sync object.
+/// http://docs.gl/gl4/glDeleteSync
+#[allow(non_snake_case, clippy::not_unsafe_ptr_arg_deref)]
+pub fn glDeleteSync(sync: GLsync) {
+	unsafe { gl::DeleteSync(sync) };
+	check::gl_error()
+}
@@ -1,9 +1,11 @@
 mod dvec;
 mod ivec;
+mod mat;
 mod uvec;
 mod vec;
 
 pub use dvec::*;
 pub use ivec::*;
+pub use mat::*;
 pub use uvec::*;
 pub use vec::*;
@@ -0,0 +1,16 @@
+use super::*;
+
+/// Column-major 2x2 matrix, like GLSL's mat2: `mat2(col0, col1)`.
+#[derive(Clone, Copy, Debug, Default)]
+#[allow(non_camel_case_types)]
+pub struct mat2(pub vec2, pub vec2);
+
+/// Column-major 3x3 matrix, like GLSL's mat3.
+#[derive(Clone, Copy, Debug, Default)]
+#[allow(non_camel_case_types)]
+pub struct mat3(pub vec3, pub vec3, pub vec3);
+
+/// Column-major 4x4 matrix, like GLSL's mat4.
+#[derive(Clone, Copy, Debug, Default)]
+#[allow(non_camel_case_types)]
+pub struct mat4(pub vec4, pub vec4, pub vec4, pub vec4);
@@ -0,0 +1,109 @@
+use super::*;
+use glutin::event::{KeyboardInput, MouseScrollDelta};
+use glutin::platform::desktop::EventLoopExtDesktop;
+use std::time::Instant;
+
+/// An interactive application, driven by `run_app`.
+/// Only `draw` is required, the other hooks default to doing nothing.
+pub trait App {
+	/// Advance the state by `dt` seconds (wall time since the previous update).
+	/// Called once per frame, before `draw`.
+	fn update(&mut self, _win: &Window, _dt: f32) {}
+
+	/// Render the current state. The buffers are swapped afterwards.
+	fn draw(&mut self, win: &Window);
+
+	/// A key was pressed or released.
+	fn on_key(&mut self, _win: &Window, _key: VirtualKeyCode, _state: ElementState) {}
+
+	/// The mouse moved, or a button was pressed or released.
+	fn on_mouse(&mut self, _win: &Window, _event: MouseEvent) {}
+
+	/// The mouse wheel was scrolled by `delta` lines, positive is away from the user.
+	fn on_scroll(&mut self, _win: &Window, _delta: f32) {}
+
+	/// The window was resized to `size` physical pixels (e.g. by the user, or when going fullscreen),
+	/// or moved to a display with a different `scale_factor`. The viewport has already been updated.
+	fn on_resize(&mut self, _win: &Window, _size: (u32, u32), _scale_factor: f64) {}
+}
+
+/// Mouse input for `App::on_mouse`.
+/// Positions are in physical pixels, from the top-left corner of the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MouseEvent {
+	Moved { x: f64, y: f64 },
+	Button { button: MouseButton, state: ElementState, x: f64, y: f64 },
+}
+
+// Pixels per line, to convert touchpad scrolling to `on_scroll` lines.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Run `app` until the window is closed: redraw continuously (paced by VSync),
+/// and forward input and resize events to its hooks.
+///
+/// Returns when the window is closed, with the GL context still current,
+/// so that the app can be dropped (deleting its GL objects) and leaks reported afterwards:
+///
+/// ```ignore
+/// let (win, mut ev) = init_gl_window(w, h, "title");
+/// let mut app = MyApp::new();
+/// run_app(&win, &mut ev, &mut app);
+/// drop(app);
+/// report_live_objects();
+/// ```
+pub fn run_app<A: App>(win: &Window, event_loop: &mut EventLoop, app: &mut A) {
+	let mut cursor = (0.0, 0.0);
+	let mut last_update = Instant::now();
+	event_loop.run_return(|event, _, control_flow| {
+		*control_flow = ControlFlow::Poll;
+		match event {
+			Event::MainEventsCleared => win.window().request_redraw(),
+			Event::RedrawRequested(_) => {
+				let now = Instant::now();
+				app.update(win, (now - last_update).as_secs_f32());
+				last_update = now;
+				app.draw(win);
+				win.swap_buffers().unwrap();
+			}
+			Event::WindowEvent { event, .. } => match event {
+				WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+				WindowEvent::Resized(size) => {
+					resize_surface(win, size);
+					unsafe { gl::Viewport(0, 0, size.width as i32, size.height as i32) };
+					app.on_resize(win, (size.width, size.height), scale_factor(win));
+				}
+				WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
+					let size = *new_inner_size;
+					resize_surface(win, size);
+					unsafe { gl::Viewport(0, 0, size.width as i32, size.height as i32) };
+					app.on_resize(win, (size.width, size.height), scale_factor);
+				}
+				WindowEvent::KeyboardInput {
+					input: KeyboardInput {
+						virtual_keycode: Some(key), state, ..
+					},
+					..
+				} => app.on_key(win, key, state),
+				WindowEvent::CursorMoved { position, .. } => {
+					cursor = (position.x, position.y);
+					app.on_mouse(win, MouseEvent::Moved { x: cursor.0, y: cursor.1 })
+				}
+				WindowEvent::MouseInput { button, state, .. } => app.on_mouse(
+					win,
+					MouseEvent::Button {
+						button,
+						state,
+						x: cursor.0,
+						y: cursor.1,
+					},
+				),
+				WindowEvent::MouseWheel { delta, .. } => match delta {
+					MouseScrollDelta::LineDelta(_, y) => app.on_scroll(win, y),
+					MouseScrollDelta::PixelDelta(p) => app.on_scroll(win, p.y as f32 / PIXELS_PER_LINE),
+				},
+				_ => (),
+			},
+			_ => (),
+		}
+	});
+}
@@ -2,23 +2,34 @@
 	Some windowing and event handling utilities.
 */
 extern crate glutin;
+
+mod app;
+mod scheduler;
+pub use app::*;
+pub use scheduler::*;
+
+use glutin::dpi::PhysicalSize;
+use glutin::window::Fullscreen;
+use glutin::{Api, GlProfile, GlRequest, NotCurrent, PossiblyCurrent};
 use std::sync::Arc;
 
-pub use glutin::event::{Event, WindowEvent};
+pub use glutin::event::{ElementState, Event, MouseButton, VirtualKeyCode, WindowEvent};
 pub use glutin::event_loop::ControlFlow;
 
 pub type Window = glutin::ContextWrapper<glutin::PossiblyCurrent, glutin::window::Window>;
 pub type EventLoop = glutin::event_loop::EventLoop<()>;
 
 /// Initialize the GL context
-/// and create a window and associated event loop.
+/// and create a resizable window and associated event loop.
+/// The size is in logical pixels, so the window has the same apparent size on HiDPI displays
+/// (see `physical_size` for the number of pixels to render).
 pub fn init_gl_window(w: u32, h: u32, title: &str) -> (Arc<Window>, EventLoop) {
-	let size = glutin::dpi::LogicalSize::new(w, h); // ?
+	let size = glutin::dpi::LogicalSize::new(w, h);
 	let event_loop = glutin::event_loop::EventLoop::new();
 	let window = glutin::window::WindowBuilder::new() //
 		.with_inner_size(size)
 		.with_title(title)
-		.with_resizable(false);
+		.with_resizable(true);
 	let gl_window = glutin::ContextBuilder::new() //
 		.with_vsync(true)
 		.build_windowed(window, &event_loop)
@@ -28,51 +39,110 @@ pub fn init_gl_window(w: u32, h: u32, title: &str) -> (Arc<Window>, EventLoop) {
 	(Arc::new(gl_window), event_loop)
 }
 
-/// Enter a continuous redraw loop, calling `draw` at VSync rate.
-/// Function returns when window is closed.
-pub fn redraw_loop<D>(gl_window: Arc<Window>, event_loop: EventLoop, draw: D)
-where
-	D: Fn(&Window) + 'static,
-{
-	event_loop.run(move |event, _, control_flow| {
-		*control_flow = ControlFlow::Wait;
-		match event {
-			Event::RedrawRequested(_) => {
-				draw(&gl_window);
-				gl_window.swap_buffers().unwrap();
-				gl_window.window().request_redraw(); //infinite redraw
-			}
-			Event::WindowEvent { event, .. } => match event {
-				WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-				_ => (),
-			},
-			Event::LoopDestroyed => return,
-			_ => (),
-		}
-	});
+/// Size of the window's drawable area in physical pixels, i.e. at the full resolution of HiDPI displays.
+pub fn physical_size(win: &Window) -> (u32, u32) {
+	let size = win.window().inner_size();
+	(size.width, size.height)
+}
+
+/// Ratio of physical to logical pixels, e.g. 2.0 on a HiDPI display.
+pub fn scale_factor(win: &Window) -> f64 {
+	win.window().scale_factor()
+}
+
+/// Resize the GL surface after a `WindowEvent::Resized` or `ScaleFactorChanged`.
+/// Required on some platforms (e.g. Wayland, macOS), harmless on others.
+pub fn resize_surface(win: &Window, size: PhysicalSize<u32>) {
+	win.resize(size)
+}
+
+/// Switch between borderless fullscreen on the window's current monitor and windowed mode.
+/// The window receives a `Resized` event.
+pub fn set_fullscreen(win: &Window, fullscreen: bool) {
+	let mode = if fullscreen { Some(Fullscreen::Borderless(None)) } else { None };
+	win.window().set_fullscreen(mode)
+}
+
+pub fn is_fullscreen(win: &Window) -> bool {
+	win.window().fullscreen().is_some()
+}
+
+/// A GL context without a window, for machines without a display (render farms, CI with Mesa llvmpipe).
+/// It has no default framebuffer to speak of: render into a `Framebuffer` instead.
+/// The context is current on the thread that created it. Drop all GL objects before dropping the context.
+pub struct HeadlessContext {
+	context: glutin::Context<PossiblyCurrent>,
+	backend: &'static str,
+	// Keeps the display connection alive (EGL surfaceless). Dropped after the context.
+	_event_loop: Option<EventLoop>,
+}
+
+impl HeadlessContext {
+	/// The API that created the context, e.g. "osmesa", for logging.
+	pub fn backend(&self) -> &'static str {
+		self.backend
+	}
+
+	pub fn get_proc_address(&self, symbol: &str) -> *const std::ffi::c_void {
+		self.context.get_proc_address(symbol)
+	}
+}
+
+/// Initialize a GL 4.5 core context without a window, of nominal size `w` x `h`,
+/// and load the GL function pointers like `init_gl_window` does.
+///
+/// On Linux, this uses OSMesa (libOSMesa.so, e.g. Mesa llvmpipe), which needs no display at all,
+/// unless a display server is available, in which case EGL surfaceless is used (no window is opened).
+/// The environment variable GL_WIN_HEADLESS=osmesa or GL_WIN_HEADLESS=surfaceless overrides the choice.
+///
+/// The context is made current on the calling thread, and must be used and dropped there.
+/// On Linux that may be any thread (e.g. a test), unlike `init_gl_window`, which needs the main thread.
+pub fn init_gl_headless(w: u32, h: u32) -> Result<HeadlessContext, String> {
+	let (context, backend, event_loop) = build_headless(w, h)?;
+	let context = unsafe { context.make_current() }.map_err(|(_, e)| format!("headless GL context ({}): {}", backend, e))?;
+	gl::load_with(|symbol| context.get_proc_address(symbol));
+	Ok(HeadlessContext {
+		context,
+		backend,
+		_event_loop: event_loop,
+	})
 }
 
-/// Enter an event handling loop until window is closed.
-/// `draw` is called when a redraw is required.
-/// `handle_event` is called on each window event.
-pub fn run_event_loop<D, E>(gl_window: Arc<Window>, event_loop: EventLoop, draw: D, handle_event: E)
-where
-	D: Fn() + 'static,
-	E: Fn(&Window, WindowEvent) + 'static,
-{
-	event_loop.run(move |event, _, control_flow| {
-		*control_flow = ControlFlow::Wait;
-		match event {
-			Event::RedrawRequested(_) => {
-				draw();
-				gl_window.swap_buffers().unwrap();
-			}
-			Event::WindowEvent { event, .. } => match event {
-				WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-				e => handle_event(&gl_window, e),
-			},
-			Event::LoopDestroyed => return,
-			_ => (),
+#[cfg(target_os = "linux")]
+fn build_headless(w: u32, h: u32) -> Result<(glutin::Context<NotCurrent>, &'static str, Option<EventLoop>), String> {
+	use glutin::platform::unix::EventLoopExtUnix;
+	use glutin::platform::unix::HeadlessContextExt;
+	use std::env;
+
+	let has_display = env::var_os("DISPLAY").is_some() || env::var_os("WAYLAND_DISPLAY").is_some();
+	let backend = match env::var("GL_WIN_HEADLESS") {
+		Ok(backend) => backend,
+		Err(_) if has_display => "surfaceless".into(),
+		Err(_) => "osmesa".into(),
+	};
+	match backend.as_str() {
+		"osmesa" => {
+			let context = headless_builder().build_osmesa(PhysicalSize::new(w, h));
+			Ok((context.map_err(|e| format!("headless GL context (osmesa, needs libOSMesa.so): {}", e))?, "osmesa", None))
+		}
+		"surfaceless" => {
+			let event_loop = EventLoop::new_any_thread(); // EventLoop::new panics off the main thread
+			let context = headless_builder().build_surfaceless(&event_loop);
+			Ok((context.map_err(|e| format!("headless GL context (surfaceless): {}", e))?, "surfaceless", Some(event_loop)))
 		}
-	});
+		other => Err(format!("GL_WIN_HEADLESS: unknown backend {:?}, want \"osmesa\" or \"surfaceless\"", other)),
+	}
+}
+
+#[cfg(not(target_os = "linux"))]
+fn build_headless(w: u32, h: u32) -> Result<(glutin::Context<NotCurrent>, &'static str, Option<EventLoop>), String> {
+	let event_loop = EventLoop::new();
+	let context = headless_builder().build_headless(&event_loop, PhysicalSize::new(w, h));
+	Ok((context.map_err(|e| format!("headless GL context: {}", e))?, "headless", Some(event_loop)))
+}
+
+fn headless_builder() -> glutin::ContextBuilder<'static, NotCurrent> {
+	glutin::ContextBuilder::new() //
+		.with_gl(GlRequest::Specific(Api::OpenGl, (4, 5)))
+		.with_gl_profile(GlProfile::Core)
 }
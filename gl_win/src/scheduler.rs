@@ -0,0 +1,267 @@
+use std::time::Duration;
+use std::time::Instant;
+
+/// Decides how many fixed-size time steps to take each frame, so that simulated time
+/// advances at a target rate (simulated time units per wall-clock second), independent of the frame rate.
+///
+/// When the GPU cannot keep up, at most `max_steps_per_frame` steps are taken and the remaining
+/// backlog is dropped: the simulation then runs slower than the target rate, rather than
+/// taking ever more steps per frame trying to catch up.
+///
+/// Time can be paused (optionally taking single steps), and reversed: then the simulated time decreases,
+/// and the integrator should use `signed_dt`.
+pub struct Scheduler {
+	dt: f64,
+	rate: f64,
+	max_steps: u32,
+	backlog: f64,
+	time: f64,
+	total_steps: u64,
+	lagging: bool,
+	paused: bool,
+	single_step: bool,
+	reversed: bool,
+
+	// measurement window for fps and steps per second
+	window_start: Instant,
+	window_frames: u32,
+	window_steps: u32,
+	fps: f64,
+	steps_per_second: f64,
+}
+
+// Length of the window over which fps and steps per second are averaged.
+const MEASURE_WINDOW: Duration = Duration::from_millis(500);
+
+impl Scheduler {
+	/// Advance time in fixed steps of `dt`, at `rate` simulated time units per wall second.
+	/// Panics if `dt` is not positive.
+	pub fn new(dt: f64, rate: f64) -> Self {
+		assert!(dt > 0.0, "Scheduler: time step must be positive, got {}", dt);
+		Self {
+			dt,
+			rate,
+			max_steps: 100,
+			backlog: 0.0,
+			time: 0.0,
+			total_steps: 0,
+			lagging: false,
+			paused: false,
+			single_step: false,
+			reversed: false,
+			window_start: Instant::now(),
+			window_frames: 0,
+			window_steps: 0,
+			fps: 0.0,
+			steps_per_second: 0.0,
+		}
+	}
+
+	/// Maximum number of steps per frame (default 100).
+	pub fn max_steps_per_frame(mut self, max_steps: u32) -> Self {
+		self.max_steps = max_steps;
+		self
+	}
+
+	/// Number of steps to take this frame, `wall_dt` seconds after the previous frame.
+	pub fn steps(&mut self, wall_dt: f32) -> u32 {
+		if self.paused {
+			let steps = if self.single_step { 1 } else { 0 };
+			self.single_step = false;
+			self.lagging = false;
+			self.advance(steps);
+			return steps;
+		}
+		self.backlog += (wall_dt as f64) * self.rate;
+		let wanted = (self.backlog / self.dt).floor();
+		let steps = wanted.min(self.max_steps as f64) as u32;
+		self.lagging = wanted > (self.max_steps as f64);
+		if self.lagging {
+			self.backlog %= self.dt; // drop what we cannot catch up with
+		} else {
+			self.backlog -= (steps as f64) * self.dt;
+		}
+		self.advance(steps);
+		steps
+	}
+
+	fn advance(&mut self, steps: u32) {
+		self.time += (steps as f64) * self.signed_dt();
+		self.total_steps += steps as u64;
+		self.measure(steps);
+	}
+
+	fn measure(&mut self, steps: u32) {
+		self.window_frames += 1;
+		self.window_steps += steps;
+		let elapsed = self.window_start.elapsed();
+		if elapsed >= MEASURE_WINDOW {
+			let secs = elapsed.as_secs_f64();
+			self.fps = (self.window_frames as f64) / secs;
+			self.steps_per_second = (self.window_steps as f64) / secs;
+			self.window_start = Instant::now();
+			self.window_frames = 0;
+			self.window_steps = 0;
+		}
+	}
+
+	/// Simulated time per step.
+	pub fn dt(&self) -> f64 {
+		self.dt
+	}
+
+	/// `dt`, negative when time is reversed.
+	pub fn signed_dt(&self) -> f64 {
+		if self.reversed {
+			-self.dt
+		} else {
+			self.dt
+		}
+	}
+
+	/// Change the time step, keeping the rate (so the number of steps per frame changes instead).
+	/// Panics if `dt` is not positive (use `set_reversed` to run time backwards).
+	pub fn set_dt(&mut self, dt: f64) {
+		assert!(dt > 0.0, "Scheduler: time step must be positive, got {}", dt);
+		self.dt = dt;
+		self.backlog = 0.0;
+	}
+
+	/// Target simulated time per wall second.
+	pub fn rate(&self) -> f64 {
+		self.rate
+	}
+
+	pub fn set_rate(&mut self, rate: f64) {
+		self.rate = rate;
+	}
+
+	/// Stop (or resume) advancing time.
+	pub fn set_paused(&mut self, paused: bool) {
+		self.paused = paused;
+		self.backlog = 0.0;
+	}
+
+	pub fn is_paused(&self) -> bool {
+		self.paused
+	}
+
+	/// While paused, take exactly one step on the next frame.
+	pub fn single_step(&mut self) {
+		self.single_step = true;
+	}
+
+	/// Run time backwards (or forwards again).
+	pub fn set_reversed(&mut self, reversed: bool) {
+		self.reversed = reversed;
+	}
+
+	pub fn is_reversed(&self) -> bool {
+		self.reversed
+	}
+
+	/// Simulated time so far (decreasing while reversed).
+	pub fn time(&self) -> f64 {
+		self.time
+	}
+
+	/// Total number of steps so far.
+	pub fn total_steps(&self) -> u64 {
+		self.total_steps
+	}
+
+	/// Whether the last frame needed more than the maximum number of steps,
+	/// i.e. the simulation is running slower than the target rate.
+	pub fn is_lagging(&self) -> bool {
+		self.lagging
+	}
+
+	/// Measured frames per second (averaged over the last half second).
+	pub fn fps(&self) -> f64 {
+		self.fps
+	}
+
+	/// Measured steps per second (averaged over the last half second).
+	pub fn steps_per_second(&self) -> f64 {
+		self.steps_per_second
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn steps_at_rate() {
+		let mut s = Scheduler::new(0.25, 1.0);
+		assert_eq!(s.steps(0.5), 2);
+		s.set_rate(2.0);
+		assert_eq!(s.steps(0.5), 4);
+		assert_eq!(s.total_steps(), 6);
+		assert_eq!(s.time(), 1.5);
+		assert!(!s.is_lagging());
+	}
+
+	#[test]
+	fn backlog_carries_over() {
+		let mut s = Scheduler::new(0.25, 1.0);
+		assert_eq!(s.steps(0.375), 1); // 0.125 left over
+		assert_eq!(s.steps(0.125), 1);
+		assert_eq!(s.steps(0.125), 0);
+		assert_eq!(s.steps(0.125), 1);
+	}
+
+	#[test]
+	fn max_steps_drops_backlog() {
+		let mut s = Scheduler::new(0.25, 1.0).max_steps_per_frame(3);
+		assert_eq!(s.steps(2.0), 3);
+		assert!(s.is_lagging());
+		// the 5 steps we could not take are not caught up with later
+		assert_eq!(s.steps(0.25), 1);
+		assert!(!s.is_lagging());
+		assert_eq!(s.time(), 1.0);
+	}
+
+	#[test]
+	fn pause_and_single_step() {
+		let mut s = Scheduler::new(0.25, 1.0);
+		s.set_paused(true);
+		assert_eq!(s.steps(1.0), 0);
+		assert_eq!(s.time(), 0.0);
+		s.single_step();
+		assert_eq!(s.steps(1.0), 1);
+		assert_eq!(s.steps(1.0), 0);
+		assert_eq!(s.time(), 0.25);
+		// time passed while paused is not caught up with
+		s.set_paused(false);
+		assert_eq!(s.steps(0.25), 1);
+		assert_eq!(s.total_steps(), 2);
+	}
+
+	#[test]
+	fn reversed() {
+		let mut s = Scheduler::new(0.25, 1.0);
+		assert_eq!(s.steps(1.0), 4);
+		s.set_reversed(true);
+		assert_eq!(s.signed_dt(), -0.25);
+		assert_eq!(s.steps(0.5), 2);
+		assert_eq!(s.time(), 0.5);
+		assert_eq!(s.steps(1.0), 4);
+		assert_eq!(s.time(), -0.5);
+		assert_eq!(s.total_steps(), 10);
+		s.set_reversed(false);
+		assert_eq!(s.signed_dt(), 0.25);
+	}
+
+	#[test]
+	#[should_panic]
+	fn zero_dt() {
+		Scheduler::new(0.0, 1.0);
+	}
+
+	#[test]
+	#[should_panic]
+	fn set_negative_dt() {
+		Scheduler::new(0.25, 1.0).set_dt(-0.25);
+	}
+}
@@ -1,12 +1,10 @@
 /*
-	Acceleration function for 2D linear waves + damping.
+	Gravitational acceleration towards a number of point masses (attractor.glsl).
 
-	Acceleration = -gradient(height) - damping * velocity
+	Acceleration = sum over attractors of -mass * r / |r|^3
 
-	This is in "natural" units, leading to unit wave speed.
-	Actual wave speed can be controlled by the time step (verlet.glsl).
-
-	https://en.wikipedia.org/wiki/Wave_equation
+	with r the particle position relative to the attractor.
+	Attractors with negative mass repel.
 */
 #version 450 core
 
@@ -15,14 +13,26 @@ layout (local_size_x = 16, local_size_y = 16) in;
 layout (binding = 0, rg32f) uniform readonly  image2D position;
 layout (binding = 1, rg32f) uniform writeonly image2D acceleration;
 
-uniform vec2 sun_pos = vec2(0.0, 0.0);
+#include "attractor.glsl"
+
+uniform uint num_attractors = 0;
+
+// Keeps the acceleration finite when a particle hits an attractor.
+#define SOFTENING (1e-3)
 
 void main(){
 	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
-
-	vec2 p = imageLoad(position, xy).xy - sun_pos;
-	float r = length(p);
-	vec2 a = -p / (r*r*r);
+	if (any(greaterThanEqual(xy, imageSize(position)))) {
+		return; // the dispatch is rounded up to whole work groups
+	}
+
+	vec2 p = imageLoad(position, xy).xy;
+	vec2 a = vec2(0.0, 0.0);
+	for (uint i = 0; i < num_attractors; i++) {
+		vec2 r = p - attractors[i].pos;
+		float r2 = dot(r, r) + SOFTENING * SOFTENING;
+		a -= attractors[i].mass * r / (r2 * sqrt(r2));
+	}
 
 	imageStore(acceleration, xy, vec4(a, 0.0, 0.0));
-}
\ No newline at end of file
+}
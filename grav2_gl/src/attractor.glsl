@@ -0,0 +1,12 @@
+/*
+	Point masses that attract the particles (accel.glsl), or repel them if their mass is negative.
+	Also drawn as markers (marker.vert).
+*/
+
+// struct Attractor { vec2 pos; float mass; },
+// declared by #[derive(Std430)] on `Attractor` in main.rs and injected by the preprocessor.
+ATTRACTOR_STRUCT
+
+layout (std430, binding = 2) readonly buffer Attractors {
+	Attractor attractors[];
+};
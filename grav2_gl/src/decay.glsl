@@ -1,24 +1,40 @@
 /*
-	Divide the colors in an unsigned RGB image by 2.
+	Multiply the density map by `decay` (< 1).
 
-	Used to smooth the photon map over time:
-	instead of resetting it to zero every time, we
-	divide it by 2. So the final photon map
-	holds the current light + 1/2 the light of the
-	previous time step, + 1/4 of the step before that + ...
+	Used to smooth the density map over time:
+	instead of resetting it to zero every frame, we
+	scale it down, so that it holds an exponential moving average
+	of the particle density (decay = exp(-frame time / time constant),
+	see State::update_density).
 
 	This kind filtering is cheap and yields a modest
 	improvement in visual quality (reduces noise).
+
+	The product is computed in 16.16 fixed point and rounded to nearest
+	(going through float would lose precision above 2^24, and truncating
+	would bias the average down), but a non-zero value always decreases
+	by at least one, so that small values cannot get stuck where rounding
+	would keep them.
 */
 #version 450 core
 
-layout(binding = 0, rgba8ui) uniform uimage2D dst;
+layout(binding = 0, r32ui) uniform uimage2D dst;
 
 layout (local_size_x = 16, local_size_y = 16) in;
 
+uniform float decay = 0.5;
+
 void main() {
 	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
-	uvec3 v = imageLoad(dst, xy).rgb;
-	v = v / 2;
-	imageStore(dst, xy, uvec4(v, 0));
-}
\ No newline at end of file
+	if (any(greaterThanEqual(xy, imageSize(dst)))) {
+		return; // the dispatch is rounded up to whole work groups
+	}
+	uint v = imageLoad(dst, xy).r;
+	uint d = uint(clamp(decay, 0.0, 1.0) * 65536.0 + 0.5); // decay in 16.16 fixed point
+	// v * d / 2^16, rounded, without overflowing 32 bits: split v into its high and low 16 bits.
+	uint r = (v >> 16) * d + (((v & 0xffffu) * d + 0x8000u) >> 16);
+	if (v > 0u && d < 65536u) {
+		r = min(r, v - 1u);
+	}
+	imageStore(dst, xy, uvec4(r));
+}
@@ -1,19 +1,47 @@
 /*
+	Map the particle density (particles per pixel, see fixed_point.glsl)
+	to a brightness, with one of the tone maps:
 
+		LINEAR: exposure * d
+		LOG:    exposure * log(1 + d)
+		ASINH:  exposure * asinh(d)  (linear at low density, logarithmic at high density)
+		AUTO:   log(1 + d) / log(1 + white), with `white` the (smoothed) maximum density,
+		        so that the densest pixel is always at full brightness.
 */
 #version 450 core
 
 in  vec2 frag_tex_coord;
 out vec4 output_color;
 
-layout(binding = 3) uniform usampler2D photon; // photon map (see photon.glsl)
+#include "fixed_point.glsl"
+
+// Values of tone_map, see ToneMap in main.rs.
+#define LINEAR 0u
+#define LOG    1u
+#define ASINH  2u
+#define AUTO   3u
+
+layout(binding = 3) uniform usampler2D density; // density map (see density.glsl)
+
+uniform uint  tone_map = LOG;
+uniform float exposure = 0.5;
+uniform float white    = ONE; // maximum density for AUTO, in fixed point units
+
+float brightness(float d) {
+	switch (tone_map) {
+	case LINEAR:
+		return exposure * d;
+	case ASINH:
+		return exposure * asinh(d);
+	case AUTO:
+		return log(1.0 + d) / log(1.0 + max(white / ONE, 1.0));
+	default:
+		return exposure * log(1.0 + d);
+	}
+}
 
 void main() {
-	vec2 start = frag_tex_coord;
-	uvec3 rgb = texture(photon, start).rgb;
-	uint num_particles = rgb.r | rgb.g << 8 | rgb.b << 16;
-	float density = float(num_particles);
-	float gamma = sqrt(density) * 0.35;
+	float d = float(texture(density, frag_tex_coord).r) / ONE;
 	vec3 color = vec3(1.0, 0.5, 0.2);
-	output_color = vec4(gamma * color, 1.0);
-}
\ No newline at end of file
+	output_color = vec4(brightness(d) * color, 1.0);
+}
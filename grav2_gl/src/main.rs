@@ -9,15 +9,12 @@ use gl::*;
 use gl_obj::*;
 use gl_safe::*;
 use gl_win::*;
-use glutin::event::ElementState;
-use glutin::event::MouseButton;
-use glutin::event::MouseScrollDelta;
-use glutin::event::VirtualKeyCode;
 use rand::prelude::*;
 use std::cell::Cell;
 use std::f32::consts::PI;
-use std::sync::Arc;
-use std::time;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::str::FromStr;
 use structopt::StructOpt;
 
 /// OpenGL water simulation.
@@ -35,90 +32,515 @@ struct Args {
 	#[structopt(long, default_value = "0.001")]
 	dt: f32,
 
-	/// Time steps per rendered frame.
+	/// Simulated time per wall-clock second.
+	#[structopt(long, default_value = "0.6")]
+	rate: f64,
+
+	/// Maximum time steps per frame, when the GPU cannot keep up with --rate.
+	#[structopt(long, default_value = "100")]
+	max_steps: u32,
+
+	/// Time steps per frame with --headless.
 	#[structopt(short, long, default_value = "10")]
 	steps_per_frame: u32,
 
-	/// Render scaling
+	/// Initial zoom, in (logical) pixels per unit length.
 	#[structopt(long, default_value = "200.0")]
 	scale: f32,
+
+	/// Time constant (seconds) of the exponential decay of the density map:
+	/// longer shows longer trails, with less noise.
+	#[structopt(long, default_value = "0.025")]
+	decay_time: f32,
+
+	/// Tone mapping of the density: linear, log, asinh or auto (cycle with T).
+	#[structopt(long, default_value = "log")]
+	tone_map: ToneMap,
+
+	/// Brightness for the linear, log and asinh tone maps (change with PageUp/PageDown).
+	#[structopt(long, default_value = "0.5")]
+	exposure: f32,
+
+	/// Start in fullscreen mode (toggle with F11).
+	#[structopt(long)]
+	fullscreen: bool,
+
+	/// Periodically print the GPU time spent in each pass.
+	#[structopt(long)]
+	profile: bool,
+
+	/// Print the uniforms, blocks and images declared by each shader program.
+	#[structopt(long)]
+	shader_info: bool,
+
+	/// Load shaders from this directory instead of the built-in ones,
+	/// and reload them when they are changed.
+	#[structopt(long)]
+	shader_dir: Option<PathBuf>,
+
+	/// Run without a window (e.g. on a machine without display):
+	/// simulate --frames frames, save the last one to --output and exit.
+	#[structopt(long)]
+	headless: bool,
+
+	/// Number of frames to simulate with --headless.
+	#[structopt(long, default_value = "100")]
+	frames: u32,
+
+	/// Image file for the last frame with --headless.
+	#[structopt(long, default_value = "gravity.png")]
+	output: String,
 }
 
 fn main() {
 	//hello();
 	let args = Args::from_args();
 
+	if args.headless {
+		let context = init_gl_headless(args.width, args.height).unwrap_or_else(|e| panic!("{}", e));
+		println!("headless GL context ({})", context.backend());
+		run_headless(&args);
+		return;
+	}
+
 	// window
 	let size = uvec2(args.width, args.height);
 	let (w, h) = (size.0, size.1);
-	let (win, ev) = init_gl_window(w, h, "gravity");
+	let (win, mut ev) = init_gl_window(w, h, "gravity");
+
+	if args.fullscreen {
+		set_fullscreen(&win, true);
+	}
 
 	// water state
-	let s = State::new(&args);
-
-	//s.p_accel //
-	//.set1f("damping", args.damping);
-	s.p_verlet.set1f("dt", args.dt);
-
-	// continuously pump redraws
-	let proxy = ev.create_proxy();
-	std::thread::spawn(move || loop {
-		proxy.send_event(()).expect("send event");
-		std::thread::sleep(time::Duration::from_millis(6));
-	});
-
-	// infinite event loop
-	//std::thread::sleep(std::time::Duration::from_millis(5000));
-	run_event_loop(ev, win, s);
+	let (w, h) = physical_size(&win);
+	let mut s = State::new(&args, uvec2(w, h), scale_factor(&win) as f32);
+	if args.shader_info {
+		s.print_shader_info();
+	}
+
+	run_app(&win, &mut ev, &mut s);
+
+	// Delete all GL objects while the context is still current, then check for leaks.
+	drop(s);
+	report_live_objects();
 }
 
-struct State {
+// Simulate without a window, and save the last frame.
+// The headless context must be current.
+fn run_headless(args: &Args) {
+	let mut s = State::new(args, uvec2(args.width, args.height), 1.0);
+	if args.shader_info {
+		s.print_shader_info();
+	}
+	for _ in 0..args.frames {
+		s.advance(args.steps_per_frame, HEADLESS_FRAME_TIME);
+	}
+	s.save_frame(&args.output);
+	drop(s);
+	report_live_objects();
+}
+
+// Wall time per frame assumed with --headless, for the decay of the density map.
+const HEADLESS_FRAME_TIME: f32 = 1.0 / 60.0;
+
+// Time constant (seconds) with which the auto tone map adapts to a change in maximum density.
+const ADAPTATION_TIME: f32 = 0.5;
+
+/// Curve from particle density to brightness, see draw.frag.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ToneMap {
+	Linear,
+	Log,
+	Asinh,
+	Auto,
+}
+
+impl ToneMap {
+	const ALL: [ToneMap; 4] = [ToneMap::Linear, ToneMap::Log, ToneMap::Asinh, ToneMap::Auto];
+
+	// The next tone map, cycling back to the first.
+	fn next(self) -> Self {
+		Self::ALL[(self as usize + 1) % Self::ALL.len()]
+	}
+}
+
+impl FromStr for ToneMap {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"linear" => Ok(ToneMap::Linear),
+			"log" => Ok(ToneMap::Log),
+			"asinh" => Ok(ToneMap::Asinh),
+			"auto" => Ok(ToneMap::Auto),
+			_ => Err(format!("unknown tone map `{}`, want linear, log, asinh or auto", s)),
+		}
+	}
+}
+
+/// Vertex of the full-screen quad, see texture.vert.
+#[derive(Clone, Copy, Vertex)]
+struct QuadVertex {
+	vertex_pos: vec2,
+	vertex_tex_coord: vec2,
+}
+
+impl QuadVertex {
+	fn new(vertex_pos: vec2, vertex_tex_coord: vec2) -> Self {
+		Self { vertex_pos, vertex_tex_coord }
+	}
+}
+
+/// A point mass that attracts the particles, or repels them if the mass is negative.
+/// See attractor.glsl.
+#[derive(Clone, Copy, Std430)]
+struct Attractor {
+	pos: vec2,
+	mass: f32,
+}
+
+// Capacity of the attractor buffer.
+const MAX_ATTRACTORS: usize = 64;
+
+// Distance (in logical pixels) within which a click selects an attractor.
+const PICK_RADIUS: f32 = 12.0;
+
+// Reductions in flight for following the center of mass and for the auto tone map (see AsyncReadback).
+const READBACK_DEPTH: usize = 2;
+
+/// 2D view of the simulation. Window position (in physical pixels, y down) of simulation coordinates `pos`:
+///
+///   (pos - center) * scale + viewport / 2
+///
+/// as used by density.glsl (uniforms `offset` = center, `scale`) and marker.vert,
+/// and inversely to find what is under the cursor.
+#[derive(Clone, Copy, Debug)]
+struct Camera {
+	/// Simulation coordinates at the center of the window.
+	center: vec2,
+	/// Physical pixels per unit length.
 	scale: f32,
-	p_accel: Program,
-	p_verlet: Program,
-	p_render: Program,
-	p_density: Program,
-	p_decay: Program,
-	pos: Texture,
-	vel: Texture,
-	acc: Texture,
-	density: Texture,
+}
+
+impl Camera {
+	fn new(scale: f32) -> Self {
+		Self { center: vec2(0.0, 0.0), scale }
+	}
+
+	// Simulation coordinates of a window position.
+	fn unproject(&self, viewport: uvec2, x: f64, y: f64) -> vec2 {
+		let x = (x as f32 - (viewport.0 / 2) as f32) / self.scale + self.center.0;
+		let y = (y as f32 - (viewport.1 / 2) as f32) / self.scale + self.center.1;
+		vec2(x, y)
+	}
+
+	// Window position of simulation coordinates.
+	fn project(&self, viewport: uvec2, pos: vec2) -> (f32, f32) {
+		let x = (pos.0 - self.center.0) * self.scale + (viewport.0 / 2) as f32;
+		let y = (pos.1 - self.center.1) * self.scale + (viewport.1 / 2) as f32;
+		(x, y)
+	}
+
+	// Move the view along with the cursor, which moved by (dx, dy) pixels.
+	fn pan(&mut self, dx: f64, dy: f64) {
+		self.center = vec2(self.center.0 - dx as f32 / self.scale, self.center.1 - dy as f32 / self.scale);
+	}
+
+	// Zoom in by `factor`, keeping the point under window position (x, y) in place.
+	fn zoom_at(&mut self, viewport: uvec2, factor: f32, x: f64, y: f64) {
+		let before = self.unproject(viewport, x, y);
+		self.scale *= factor;
+		let after = self.unproject(viewport, x, y);
+		self.center = vec2(self.center.0 + before.0 - after.0, self.center.1 + before.1 - after.1);
+	}
+
+	// Center on the box from `min` to `max`, and zoom to fit it in the viewport with a small margin.
+	fn fit(&mut self, viewport: uvec2, min: vec2, max: vec2) {
+		self.center = vec2(0.5 * (min.0 + max.0), 0.5 * (min.1 + max.1));
+		let size = vec2((max.0 - min.0).max(1e-6), (max.1 - min.1).max(1e-6));
+		self.scale = 0.95 * f32::min(viewport.0 as f32 / size.0, viewport.1 as f32 / size.1);
+	}
+}
+
+// Pan in progress: where the right or middle button was pressed, and the last cursor position.
+struct Pan {
+	start: (f64, f64),
+	last: (f64, f64),
+}
+
+struct State {
+	camera: Camera,
+	scale_factor: f32,
+	attractors: Vec<Attractor>,
+	attractor_buf: StorageBuffer<Attractor>,
+	repulsive: bool,
+	dragging: Option<usize>,
+	panning: Option<Pan>,
+	follow: bool,
+	com_readback: AsyncReadback<vec2>,
+	white_readback: AsyncReadback<u32>,
+	cursor: (f64, f64),
+	compute: ComputeLib,
+	p_accel: HotProgram,
+	p_verlet: HotProgram,
+	p_render: HotProgram,
+	p_density: HotProgram,
+	p_decay: HotProgram,
+	p_marker: HotProgram,
+	u_num_attractors: Uniform<u32>,
+	u_scale: Uniform<f32>,
+	u_offset: Uniform<vec2>,
+	u_dt: Uniform<f32>,
+	u_drift: Uniform<f32>,
+	u_decay: Uniform<f32>,
+	u_weight: Uniform<f32>,
+	u_tone_map: Uniform<u32>,
+	u_exposure: Uniform<f32>,
+	u_white: Uniform<f32>,
+	u_marker_scale: Uniform<f32>,
+	u_marker_offset: Uniform<vec2>,
+	u_marker_viewport: Uniform<vec2>,
+	u_point_size: Uniform<f32>,
+	pos: PingPong<Texture<Rg32f>>,
+	vel: PingPong<Texture<Rg32f>>,
+	acc: Texture<Rg32f>,
+	density: Texture<R32ui>,
+	decay_time: f32,
+	tone_map: ToneMap,
+	exposure: f32,
+	white: f32,
 	vao: VertexArray,
-	time_steps_per_draw: u32,
-	mouse_down: bool,
+	_vertex_buffer: Buffer<QuadVertex>,
+	marker_vao: VertexArray,
+	scheduler: Scheduler,
 	frames: Cell<i32>,
+	profiler: GpuProfiler,
+	profile: bool,
+	screenshot_requested: bool,
 }
 
 impl State {
-	fn new(args: &Args) -> Self {
+	// `view_size`: size of the window in physical pixels,
+	// `scale_factor`: ratio of physical to logical pixels (HiDPI).
+	fn new(args: &Args, view_size: uvec2, scale_factor: f32) -> Self {
 		let (pos, vel) = Self::initial_particles(&args);
 		let size = uvec2(args.width, args.height);
-		let p_render = Program::new(&[
-			//
-			Shader::new_vert(include_str!("texture.vert")),
-			Shader::new_frag(include_str!("draw.frag")),
-		]);
-
-		Self {
-			scale: args.scale,
-			p_accel: Self::compute_prog(include_str!("accel.glsl")),
-			p_verlet: Self::compute_prog(include_str!("verlet.glsl")),
-			p_decay: Self::compute_prog(include_str!("decay.glsl")),
-			p_density: Self::compute_prog(include_str!("density.glsl")),
+		let shaders = Rc::new(Self::shaders(args.shader_dir.as_ref()));
+		let p_render = Self::hot_prog(&shaders, &[(VERTEX_SHADER, "texture.vert"), (FRAGMENT_SHADER, "draw.frag")]);
+		let (vao, vertex_buffer) = Self::vao(&p_render).unwrap_or_else(|e| panic!("{}", e));
+		let p_accel = Self::hot_prog(&shaders, &[(COMPUTE_SHADER, "accel.glsl")]);
+		let p_verlet = Self::hot_prog(&shaders, &[(COMPUTE_SHADER, "verlet.glsl")]);
+		let p_density = Self::hot_prog(&shaders, &[(COMPUTE_SHADER, "density.glsl")]);
+		let p_decay = Self::hot_prog(&shaders, &[(COMPUTE_SHADER, "decay.glsl")]);
+		let p_marker = Self::hot_prog(&shaders, &[(VERTEX_SHADER, "marker.vert"), (FRAGMENT_SHADER, "marker.frag")]);
+		let unused = Attractor { pos: vec2(0.0, 0.0), mass: 0.0 };
+
+		let s = Self {
+			camera: Camera::new(args.scale * scale_factor),
+			scale_factor,
+			attractors: vec![Attractor { pos: vec2(0.0, 0.0), mass: 1.0 }],
+			attractor_buf: StorageBuffer::new(&[unused; MAX_ATTRACTORS], DYNAMIC_STORAGE_BIT),
+			repulsive: false,
+			dragging: None,
+			panning: None,
+			follow: false,
+			com_readback: AsyncReadback::new(1, READBACK_DEPTH),
+			white_readback: AsyncReadback::new(1, READBACK_DEPTH),
+			cursor: (0.0, 0.0),
+			compute: ComputeLib::new(),
+			u_num_attractors: p_accel.uniform("num_attractors"),
+			u_scale: p_density.uniform("scale"),
+			u_offset: p_density.uniform("offset"),
+			u_dt: p_verlet.uniform("dt"),
+			u_drift: p_verlet.uniform("drift"),
+			u_decay: p_decay.uniform("decay"),
+			u_weight: p_density.uniform("weight"),
+			u_tone_map: p_render.uniform("tone_map"),
+			u_exposure: p_render.uniform("exposure"),
+			u_white: p_render.uniform("white"),
+			u_marker_scale: p_marker.uniform("scale"),
+			u_marker_offset: p_marker.uniform("offset"),
+			u_marker_viewport: p_marker.uniform("viewport"),
+			u_point_size: p_marker.uniform("point_size"),
+			p_accel,
+			p_verlet,
+			p_decay,
+			p_density,
 			p_render,
-			pos: Self::vec_to_tex(size, &pos),
-			vel: Self::vec_to_tex(size, &vel),
-			acc: Texture::new2d(RG32F, size),
-			density: Texture::new2d(RGBA8UI, size).filter_nearest(),
-			vao: Self::vao(p_render),
-			time_steps_per_draw: args.steps_per_frame,
+			p_marker,
+			pos: PingPong::from_data2d(size, &pos),
+			vel: PingPong::from_data2d(size, &vel),
+			acc: Texture::new2d(size),
+			density: Texture::new2d(view_size).filter_nearest(),
+			decay_time: args.decay_time,
+			tone_map: args.tone_map,
+			exposure: args.exposure,
+			white: 0.0,
+			vao,
+			_vertex_buffer: vertex_buffer,
+			marker_vao: VertexArray::create(),
+			scheduler: Scheduler::new(args.dt as f64, args.rate).max_steps_per_frame(args.max_steps),
 			frames: Cell::new(0),
-			mouse_down: false,
+			profiler: GpuProfiler::new(64).enabled(args.profile),
+			profile: args.profile,
+			screenshot_requested: false,
+		};
+		s.init_uniforms();
+		s
+	}
+
+	// Set the uniforms that do not change every frame.
+	fn init_uniforms(&self) {
+		self.u_dt.set(self.scheduler.signed_dt() as f32);
+		self.upload_attractors();
+	}
+
+	// Multiply the time step by `factor`. The rate stays the same, so this changes the number of steps per frame.
+	fn scale_dt(&mut self, factor: f64) {
+		self.scheduler.set_dt(self.scheduler.dt() * factor);
+		self.u_dt.set(self.scheduler.signed_dt() as f32);
+	}
+
+	// Toggle the direction of time.
+	// Velocities are half a step behind positions (see verlet.glsl),
+	// so first update only the velocities, to half a step ahead, then step back with -dt.
+	fn reverse(&mut self) {
+		self.u_drift.set(0.0);
+		self.update_acc();
+		self.update_pos_vel();
+		self.u_drift.set(1.0);
+		self.scheduler.set_reversed(!self.scheduler.is_reversed());
+		self.u_dt.set(self.scheduler.signed_dt() as f32);
+	}
+
+	// Copy the attractors to the GPU, after they have changed.
+	fn upload_attractors(&self) {
+		self.attractor_buf.sub_data(0, &self.attractors);
+		self.u_num_attractors.set(self.attractors.len() as u32);
+	}
+
+	// Add an attractor (attractive or repulsive according to the current mode) at `pos`,
+	// and return its index, or None if there are too many already.
+	fn add_attractor(&mut self, pos: vec2) -> Option<usize> {
+		if self.attractors.len() == MAX_ATTRACTORS {
+			eprintln!("cannot add more than {} attractors", MAX_ATTRACTORS);
+			return None;
+		}
+		let mass = if self.repulsive { -1.0 } else { 1.0 };
+		self.attractors.push(Attractor { pos, mass });
+		self.upload_attractors();
+		Some(self.attractors.len() - 1)
+	}
+
+	// Index of the attractor closest to window position (x, y), if within the pick radius.
+	fn pick(&self, x: f64, y: f64) -> Option<usize> {
+		let radius = PICK_RADIUS * self.scale_factor;
+		let dist = |a: &Attractor| {
+			let (ax, ay) = self.to_window(a.pos);
+			((ax - x as f32).powi(2) + (ay - y as f32).powi(2)).sqrt()
+		};
+		let (i, d) = self.attractors.iter().map(dist).enumerate().min_by(|a, b| a.1.total_cmp(&b.1))?;
+		if d <= radius {
+			Some(i)
+		} else {
+			None
+		}
+	}
+
+	// Simulation coordinates of a window position in physical pixels.
+	fn to_world(&self, x: f64, y: f64) -> vec2 {
+		self.camera.unproject(self.viewport(), x, y)
+	}
+
+	// Window position in physical pixels of simulation coordinates.
+	fn to_window(&self, pos: vec2) -> (f32, f32) {
+		self.camera.project(self.viewport(), pos)
+	}
+
+	// Window size in physical pixels (the density map is drawn 1:1).
+	fn viewport(&self) -> uvec2 {
+		let size = self.density.size();
+		uvec2(size.0, size.1)
+	}
+
+	// Center the camera on the mean particle position (all particles have the same mass).
+	// The sum of positions is read back asynchronously, so the camera follows a few frames late
+	// instead of stalling the pipeline every frame.
+	fn follow_center_of_mass(&mut self) {
+		if let Some(sum) = self.com_readback.try_recv_latest() {
+			let n = self.pos.front().num_texels() as f32;
+			self.camera.center = vec2(sum[0].0 / n, sum[0].1 / n);
+		}
+		if self.com_readback.pending() < READBACK_DEPTH {
+			match self.compute.reduce_texture_to_buffer(self.pos.front(), ReduceOp::Sum) {
+				Ok(sum) => {
+					self.com_readback.read_buffer(&sum);
+				}
+				Err(e) => eprintln!("follow: {}", e),
+			}
 		}
 	}
 
-	fn vec_to_tex(size: uvec2, data: &[vec2]) -> Texture {
-		Texture::new2d(RG32F, size).sub_image2d(0, 0, 0, size.0, size.1, RG, FLOAT, data)
+	// Fit the view to the bounding box of all particles.
+	fn fit_view(&mut self) -> Result<(), String> {
+		let min = self.compute.reduce_texture(self.pos.front(), ReduceOp::Min)?;
+		let max = self.compute.reduce_texture(self.pos.front(), ReduceOp::Max)?;
+		self.camera.fit(self.viewport(), min, max);
+		Ok(())
+	}
+
+	// Rebuild the programs whose shader files have changed (with --shader-dir),
+	// and re-resolve everything that refers to them.
+	fn poll_shaders(&mut self) {
+		let mut reloaded = false;
+		for p in &mut [
+			&mut self.p_accel,
+			&mut self.p_verlet,
+			&mut self.p_decay,
+			&mut self.p_density,
+			&mut self.p_render,
+			&mut self.p_marker,
+		] {
+			reloaded |= p.poll();
+		}
+		if reloaded {
+			self.u_num_attractors = self.p_accel.optional_uniform("num_attractors");
+			self.u_scale = self.p_density.optional_uniform("scale");
+			self.u_offset = self.p_density.optional_uniform("offset");
+			self.u_dt = self.p_verlet.optional_uniform("dt");
+			self.u_drift = self.p_verlet.optional_uniform("drift");
+			self.u_decay = self.p_decay.optional_uniform("decay");
+			self.u_weight = self.p_density.optional_uniform("weight");
+			self.u_tone_map = self.p_render.optional_uniform("tone_map");
+			self.u_exposure = self.p_render.optional_uniform("exposure");
+			self.u_white = self.p_render.optional_uniform("white");
+			self.u_marker_scale = self.p_marker.optional_uniform("scale");
+			self.u_marker_offset = self.p_marker.optional_uniform("offset");
+			self.u_marker_viewport = self.p_marker.optional_uniform("viewport");
+			self.u_point_size = self.p_marker.optional_uniform("point_size");
+			match Self::vao(&self.p_render) {
+				Ok((vao, vertex_buffer)) => {
+					self.vao = vao;
+					self._vertex_buffer = vertex_buffer;
+				}
+				Err(e) => eprintln!("{}", e),
+			}
+			self.init_uniforms();
+		}
+	}
+
+	fn print_shader_info(&self) {
+		for (name, p) in &[
+			("accel", &self.p_accel),
+			("verlet", &self.p_verlet),
+			("decay", &self.p_decay),
+			("density", &self.p_density),
+			("render", &self.p_render),
+			("marker", &self.p_marker),
+		] {
+			println!("{}:\n{}", name, p.info());
+		}
 	}
 
 	fn initial_particles(args: &Args) -> (Vec<vec2>, Vec<vec2>) {
@@ -145,179 +567,372 @@ impl State {
 	}
 
 	fn steps(&mut self, n: u32) {
+		// one pass for all steps: timing each step would take up to two queries per dispatch
+		self.profiler.begin("accel+verlet");
 		for _ in 0..n {
 			self.update_acc();
 			self.update_pos_vel();
 		}
-		self.update_density();
+		self.profiler.end();
 	}
 
 	fn update_acc(&self) {
-		self.pos.bind_image_unit(0, READ_ONLY);
+		self.pos.bind_front(0);
 		self.acc.bind_image_unit(1, WRITE_ONLY);
-		//self.acc.bind_image_unit(2, WRITE_ONLY);
-		self.exec(self.p_accel)
+		self.attractor_buf.bind(&self.p_accel, "Attractors").unwrap_or_else(|e| panic!("{}", e));
+		self.exec(&self.p_accel)
 	}
 
-	fn update_pos_vel(&self) {
-		self.pos.bind_image_unit(0, READ_WRITE);
-		self.vel.bind_image_unit(1, READ_WRITE);
+	fn update_pos_vel(&mut self) {
+		self.pos.bind(0, 3);
+		self.vel.bind(1, 4);
 		self.acc.bind_image_unit(2, READ_ONLY);
-		self.exec(self.p_verlet)
+		self.exec(&self.p_verlet);
+		self.pos.swap();
+		self.vel.swap();
 	}
 
-	fn update_density(&self) {
-		self.density.bind_image_unit(0, READ_WRITE);
-		self.exec(self.p_decay);
+	// Decay the density map by the time constant over `wall_dt` seconds, and add the current particles.
+	fn update_density(&mut self, wall_dt: f32) {
+		let decay = (-wall_dt / self.decay_time).exp();
 
-		self.p_density.set1f("scale", self.scale);
-		self.pos.bind_image_unit(0, READ_WRITE); // TODO
+		self.profiler.begin("decay");
+		self.u_decay.set(decay);
+		self.density.bind_image_unit(0, READ_WRITE);
+		let size = self.density.size();
+		self.exec_over(&self.p_decay, uvec2(size.0, size.1));
+		self.profiler.end();
+
+		self.profiler.begin("density");
+		self.u_scale.set(self.camera.scale);
+		self.u_offset.set(self.camera.center);
+		self.u_weight.set(1.0 - decay);
+		self.pos.bind_front(0);
 		self.density.bind_image_unit(1, READ_WRITE);
-		self.exec(self.p_density);
+		self.exec(&self.p_density);
+		self.profiler.end();
+	}
+
+	// Track the maximum density for the auto tone map, adapting smoothly over `wall_dt` seconds.
+	// Like the center of mass, the maximum is read back asynchronously, a few frames late.
+	fn update_white(&mut self, wall_dt: f32) {
+		if self.tone_map != ToneMap::Auto {
+			return;
+		}
+		if let Some(max) = self.white_readback.try_recv_latest() {
+			let max = max[0] as f32;
+			if self.white == 0.0 {
+				self.white = max;
+			}
+			self.white += (max - self.white) * (1.0 - (-wall_dt / ADAPTATION_TIME).exp());
+		}
+		if self.white_readback.pending() < READBACK_DEPTH {
+			match self.compute.reduce_texture_to_buffer(&self.density, ReduceOp::Max) {
+				Ok(max) => {
+					self.white_readback.read_buffer(&max);
+				}
+				Err(e) => eprintln!("auto exposure: {}", e),
+			}
+		}
 	}
 
-	fn draw(&self, _w: &Window) {
+	// Draw the density map and attractor markers into the current framebuffer.
+	fn render(&self) {
 		glClearColor(0.5, 0.5, 0.5, 1.0);
 		glClear(gl::COLOR_BUFFER_BIT);
 
+		self.u_tone_map.set(self.tone_map as u32);
+		self.u_exposure.set(self.exposure);
+		self.u_white.set(self.white);
 		self.p_render.use_program();
 		self.vao.bind();
 		self.density.bind_texture_unit(3);
 
 		glDrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+
+		self.render_markers();
 	}
 
-	fn exec(&self, p: Program) {
-		let xy = self.pos.size();
-		p.compute_and_sync(uvec3(xy.0, xy.1, 1))
-	}
-
-	fn on_cursor_moved(&self, position: (f64, f64)) {
-		if self.mouse_down {
-			let (w, h) = (self.pos.size().0 as i32, self.pos.size().1 as i32);
-			let (x, y) = ((position.0) as i32, (position.1) as i32);
-			if x >= 0 && x < (w as i32) && y >= 0 && y < (h as i32) {
-				let x = (x - w / 2) as f32;
-				let y = (y - h / 2) as f32;
-				let x = x / self.scale;
-				let y = y / self.scale;
-				self.p_accel.set2f("sun_pos", x, y);
-			}
+	// Draw each attractor as a point (see marker.vert), on top of the density map.
+	fn render_markers(&self) {
+		if self.attractors.is_empty() {
+			return;
 		}
+		let size = self.density.size();
+		self.u_marker_scale.set(self.camera.scale);
+		self.u_marker_offset.set(self.camera.center);
+		self.u_marker_viewport.set(vec2(size.0 as f32, size.1 as f32));
+		self.u_point_size.set(PICK_RADIUS * self.scale_factor);
+		self.attractor_buf.bind(&self.p_marker, "Attractors").unwrap_or_else(|e| panic!("{}", e));
+
+		glEnable(gl::PROGRAM_POINT_SIZE);
+		self.p_marker.use_program();
+		self.marker_vao.bind();
+		glDrawArrays(gl::POINTS, 0, self.attractors.len() as i32);
 	}
 
-	fn on_mouse_input(&mut self, _button: MouseButton, state: ElementState) {
-		self.mouse_down = match state {
-			ElementState::Pressed => true,
-			ElementState::Released => false,
-		};
+	// Run a compute program over all particles.
+	fn exec(&self, p: &Program) {
+		let xy = self.pos.size();
+		self.exec_over(p, uvec2(xy.0, xy.1))
+	}
+
+	// Run a compute program with 16x16 work groups over `size` invocations, rounded up
+	// (the shaders skip the invocations outside their image).
+	fn exec_over(&self, p: &Program, size: uvec2) {
+		let (w, h) = (size.0.div_ceil(16) * 16, size.1.div_ceil(16) * 16);
+		p.compute_and_sync(uvec3(w, h, 1)).unwrap_or_else(|e| panic!("{}", e))
 	}
 
-	fn on_redraw_requested(&mut self, win: &Window) {
-		self.draw(&win);
-		win.swap_buffers().unwrap();
-		self.steps(self.time_steps_per_draw);
+	// Simulate the time steps of one frame, `wall_dt` seconds after the previous one.
+	fn advance(&mut self, steps: u32, wall_dt: f32) {
+		self.steps(steps);
+		self.update_density(wall_dt);
+		self.update_white(wall_dt);
 		self.frames.set(self.frames.get() + 1);
+
+		self.profiler.collect();
+		if self.profile && self.frames.get() % 100 == 0 {
+			println!("{}", self.profiler);
+		}
 		//let secs = self.start.elapsed().as_secs_f32();
 		//let fps = self.frames.get() as f32 / secs;
 		//dbg!(fps);
 	}
 
-	fn zoom(&mut self, scale: f32) {
-		self.scale = self.scale * scale;
-		self.density.bind_image_unit(0, READ_WRITE);
+	// Save the window contents. Must be called after drawing, before swapping buffers.
+	fn screenshot(&mut self) {
+		self.screenshot_requested = false;
+		let size = self.density.size();
+		let fname = format!("screenshot{:06}.png", self.frames.get());
+		gl_img::save_framebuffer(&fname, &Framebuffer::window(uvec2(size.0, size.1)), gl::BACK);
+		println!("saved {}", fname);
+	}
+
+	// Render the current state into an offscreen image of the window size and save it (for --headless).
+	fn save_frame(&self, fname: &str) {
+		let size = self.density.size();
+		let color = Texture::<Rgba8>::new2d(uvec2(size.0, size.1));
+		let fb = Framebuffer::new(&color).unwrap_or_else(|e| panic!("{}", e));
+		fb.bind();
+		self.render();
+		gl_img::save_framebuffer(fname, &fb, gl::COLOR_ATTACHMENT0);
+		println!("saved {}", fname);
 	}
 
-	fn on_user_event(&self, win: &Window) {
-		win.window().request_redraw()
+	// Show the simulated time, time controls and measured performance in the window title.
+	fn show_stats(&self, win: &Window) {
+		let s = &self.scheduler;
+		let state = match (s.is_paused(), s.is_reversed()) {
+			(false, false) => "running",
+			(false, true) => "reversed",
+			(true, false) => "paused",
+			(true, true) => "paused, reversed",
+		};
+		let lagging = if s.is_lagging() { " (lagging)" } else { "" };
+		let mode = if self.repulsive { "repulsive" } else { "attractive" };
+		let title = format!(
+			"gravity: t = {:.3} ({}), dt = {:.2e}, rate = {:.3}, {:.0} fps, {:.0} steps/s{}, {} (M)",
+			s.time(),
+			state,
+			s.dt(),
+			s.rate(),
+			s.fps(),
+			s.steps_per_second(),
+			lagging,
+			mode
+		);
+		win.window().set_title(&title);
 	}
 
-	fn on_cursor_entered(&self) {}
+	// Zoom in by `factor`, keeping the point under window position (x, y) in place.
+	fn zoom_at(&mut self, factor: f32, x: f64, y: f64) {
+		self.camera.zoom_at(self.viewport(), factor, x, y);
+	}
 
-	fn on_cursor_left(&self) {}
+	// Zoom in by `factor` around the center of the window.
+	fn zoom(&mut self, factor: f32) {
+		let size = self.viewport();
+		self.zoom_at(factor, (size.0 / 2) as f64, (size.1 / 2) as f64);
+	}
 
-	fn on_mouse_wheel(&mut self, delta: MouseScrollDelta) {
-		let mut zoom = |dy| {
-			if dy > 0.0 {
-				self.zoom(1.05)
-			}
-			if dy < 0.0 {
-				self.zoom(0.95)
-			}
+	// Shader sources from `dir`, or else the ones embedded in the binary,
+	// with the declaration of `Attractor` injected as ATTRACTOR_STRUCT (see attractor.glsl).
+	fn shaders(dir: Option<&PathBuf>) -> Preprocessor {
+		let shaders = match dir {
+			Some(dir) => Preprocessor::new(DiskFs::new(dir)),
+			None => Self::embedded_shaders(),
 		};
-		match delta {
-			MouseScrollDelta::LineDelta(_x, y) => zoom(y),
-			MouseScrollDelta::PixelDelta(phys) => zoom(phys.y as f32),
+		shaders.define("ATTRACTOR_STRUCT", Attractor::glsl_declarations().replace('\n', " "))
+	}
+
+	fn embedded_shaders() -> Preprocessor {
+		Preprocessor::new(
+			EmbeddedFs::new()
+				.file("accel.glsl", include_str!("accel.glsl"))
+				.file("attractor.glsl", include_str!("attractor.glsl"))
+				.file("decay.glsl", include_str!("decay.glsl"))
+				.file("density.glsl", include_str!("density.glsl"))
+				.file("draw.frag", include_str!("draw.frag"))
+				.file("fixed_point.glsl", include_str!("fixed_point.glsl"))
+				.file("marker.frag", include_str!("marker.frag"))
+				.file("marker.vert", include_str!("marker.vert"))
+				.file("texture.vert", include_str!("texture.vert"))
+				.file("verlet.glsl", include_str!("verlet.glsl")),
+		)
+	}
+
+	fn hot_prog(shaders: &Rc<Preprocessor>, stages: &[(GLenum, &str)]) -> HotProgram {
+		HotProgram::new(shaders.clone(), stages).unwrap_or_else(|e| panic!("{}", e))
+	}
+
+	// The vertex array, and the buffer it refers to (which must be kept alive).
+	fn vao(prog: &Program) -> Result<(VertexArray, Buffer<QuadVertex>), String> {
+		let vertices = [
+			QuadVertex::new(vec2(-1.0, 1.0), vec2(0.0, 0.0)),
+			QuadVertex::new(vec2(-1.0, -1.0), vec2(0.0, 1.0)),
+			QuadVertex::new(vec2(1.0, 1.0), vec2(1.0, 0.0)),
+			QuadVertex::new(vec2(1.0, -1.0), vec2(1.0, 1.0)),
+		];
+		let buffer = Buffer::new(&vertices, 0);
+		let vao = VertexArray::from_layout(prog, &buffer)?;
+		Ok((vao, buffer))
+	}
+}
+
+impl App for State {
+	fn update(&mut self, win: &Window, dt: f32) {
+		self.poll_shaders();
+		let steps = self.scheduler.steps(dt);
+		self.advance(steps, dt);
+		if self.follow {
+			self.follow_center_of_mass();
+		}
+		if self.frames.get() % 30 == 0 {
+			self.show_stats(win);
 		}
 	}
 
-	fn on_key(&mut self, k: VirtualKeyCode) {
+	fn draw(&mut self, _win: &Window) {
+		self.profiler.begin("draw");
+		self.render();
+		self.profiler.end();
+		if self.screenshot_requested {
+			self.screenshot();
+		}
+	}
+
+	fn on_key(&mut self, win: &Window, k: VirtualKeyCode, state: ElementState) {
+		if state != ElementState::Pressed {
+			return;
+		}
 		println!("key {:?}", k);
-		//use VirtualKeyCode::*;
 		match k {
 			VirtualKeyCode::Equals | VirtualKeyCode::Plus => self.zoom(2.0),
 			VirtualKeyCode::Minus | VirtualKeyCode::Underline => self.zoom(0.5),
+			VirtualKeyCode::F => {
+				self.follow = false;
+				self.fit_view().unwrap_or_else(|e| eprintln!("fit: {}", e));
+			}
+			VirtualKeyCode::C => {
+				self.follow = !self.follow;
+				self.com_readback = AsyncReadback::new(1, READBACK_DEPTH); // drop results from when we last followed
+				println!("follow center of mass: {}", self.follow);
+			}
+			VirtualKeyCode::M => self.repulsive = !self.repulsive,
+			VirtualKeyCode::T => {
+				self.tone_map = self.tone_map.next();
+				self.white = 0.0;
+				self.white_readback = AsyncReadback::new(1, READBACK_DEPTH); // drop results from when we last used Auto
+				println!("tone map: {:?}", self.tone_map);
+			}
+			VirtualKeyCode::PageUp => {
+				self.exposure *= 1.25;
+				println!("exposure: {}", self.exposure);
+			}
+			VirtualKeyCode::PageDown => {
+				self.exposure /= 1.25;
+				println!("exposure: {}", self.exposure);
+			}
+			VirtualKeyCode::Space => self.scheduler.set_paused(!self.scheduler.is_paused()),
+			VirtualKeyCode::N | VirtualKeyCode::Period => {
+				self.scheduler.set_paused(true);
+				self.scheduler.single_step();
+			}
+			VirtualKeyCode::R => self.reverse(),
+			VirtualKeyCode::RBracket => self.scale_dt(1.25),
+			VirtualKeyCode::LBracket => self.scale_dt(1.0 / 1.25),
+			VirtualKeyCode::Up => self.scheduler.set_rate(self.scheduler.rate() * 2.0),
+			VirtualKeyCode::Down => self.scheduler.set_rate(self.scheduler.rate() / 2.0),
+			VirtualKeyCode::F11 => set_fullscreen(win, !is_fullscreen(win)),
+			VirtualKeyCode::F12 => self.screenshot_requested = true,
 			_ => (),
 		}
+		self.show_stats(win);
 	}
 
-	fn compute_prog(src: &str) -> Program {
-		Program::new(&[Shader::new_comp(src)])
-	}
-
-	fn vao(prog: Program) -> VertexArray {
-		let v_pos = [
-			//
-			vec2(-1.0, 1.0),
-			vec2(-1.0, -1.0),
-			vec2(1.0, 1.0),
-			vec2(1.0, -1.0),
-		];
-		let v_pos_buf = Buffer::new(&v_pos, 0);
-
-		let v_texc = [
-			//
-			vec2(0.0, 0.0),
-			vec2(0.0, 1.0),
-			vec2(1.0, 0.0),
-			vec2(1.0, 1.0),
-		];
-		let v_texc_buf = Buffer::new(&v_texc, 0);
-
-		let v_pos_attr = prog.attrib_location("vertex_pos").unwrap();
-		let v_texc_attr = prog.attrib_location("vertex_tex_coord").unwrap();
-		VertexArray::create()
-			.enable_attrib(v_pos_attr)
-			.attrib_format(v_pos_attr, 2, gl::FLOAT, false, 0)
-			.vertex_buffer(v_pos_attr, v_pos_buf, 0, sizeof(v_pos[0]))
-			.enable_attrib(v_texc_attr)
-			.attrib_format(v_texc_attr, 2, gl::FLOAT, false, 0)
-			.vertex_buffer(v_texc_attr, v_texc_buf, 0, sizeof(v_texc[0]))
-	}
-}
-
-fn run_event_loop(ev: EventLoop, win: Arc<Window>, mut s: State) {
-	ev.run(move |event, _, control_flow| {
-		*control_flow = ControlFlow::Wait;
+	// Left click to place an attractor, or drag an existing one.
+	// Right or middle drag to pan, right click (without dragging) to delete an attractor.
+	fn on_mouse(&mut self, _win: &Window, event: MouseEvent) {
 		match event {
-			Event::RedrawRequested(_) => s.on_redraw_requested(&win),
-			Event::UserEvent(_) => s.on_user_event(&win),
-			Event::LoopDestroyed => return,
-			Event::WindowEvent { event, .. } => match event {
-				WindowEvent::CursorMoved { position, .. } => s.on_cursor_moved((position.x, position.y)),
-				WindowEvent::MouseInput { state, button, .. } => s.on_mouse_input(button, state),
-				WindowEvent::MouseWheel { delta, .. } => s.on_mouse_wheel(delta),
-				WindowEvent::CursorEntered { .. } => s.on_cursor_entered(),
-				WindowEvent::CursorLeft { .. } => s.on_cursor_left(),
-				WindowEvent::KeyboardInput { input, .. } => {
-					if let Some(k) = input.virtual_keycode {
-						s.on_key(k)
+			MouseEvent::Moved { x, y } => {
+				self.cursor = (x, y);
+				if let Some(i) = self.dragging {
+					self.attractors[i].pos = self.to_world(x, y);
+					self.upload_attractors();
+				}
+				if let Some(pan) = &mut self.panning {
+					self.camera.pan(x - pan.last.0, y - pan.last.1);
+					pan.last = (x, y);
+					self.follow = false;
+				}
+			}
+			MouseEvent::Button { button, state, x, y } => match (button, state) {
+				(MouseButton::Left, ElementState::Pressed) => {
+					self.dragging = match self.pick(x, y) {
+						Some(i) => Some(i),
+						None => self.add_attractor(self.to_world(x, y)),
 					}
 				}
-				WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+				(MouseButton::Left, ElementState::Released) => self.dragging = None,
+				(MouseButton::Right, ElementState::Pressed) | (MouseButton::Middle, ElementState::Pressed) => self.panning = Some(Pan { start: (x, y), last: (x, y) }),
+				(MouseButton::Right, ElementState::Released) => {
+					let clicked = match self.panning.take() {
+						Some(pan) => (pan.start.0 - x).hypot(pan.start.1 - y) < 3.0 * self.scale_factor as f64,
+						None => false,
+					};
+					if let Some(i) = self.pick(x, y).filter(|_| clicked) {
+						self.attractors.remove(i);
+						self.dragging = None;
+						self.upload_attractors();
+					}
+				}
+				(MouseButton::Middle, ElementState::Released) => self.panning = None,
 				_ => (),
 			},
-			_ => (),
 		}
-	});
+	}
+
+	// Scroll over an attractor to change its mass, elsewhere to zoom around the cursor.
+	fn on_scroll(&mut self, _win: &Window, delta: f32) {
+		if let Some(i) = self.pick(self.cursor.0, self.cursor.1) {
+			self.attractors[i].mass *= 1.1f32.powf(delta);
+			self.upload_attractors();
+			return;
+		}
+		self.zoom_at(1.1f32.powf(delta), self.cursor.0, self.cursor.1);
+	}
+
+	// Reallocate the density map to the new window size (in physical pixels),
+	// so that it is drawn 1:1 and keeps its aspect ratio.
+	// Keep showing the same region when moved to a display with a different DPI.
+	fn on_resize(&mut self, _win: &Window, size: (u32, u32), scale_factor: f64) {
+		self.camera.scale *= scale_factor as f32 / self.scale_factor;
+		self.scale_factor = scale_factor as f32;
+		let current = self.density.size();
+		if size.0 == 0 || size.1 == 0 || (size.0 == current.0 && size.1 == current.1) {
+			return; // minimized, or unchanged
+		}
+		self.density = Texture::new2d(uvec2(size.0, size.1)).filter_nearest();
+	}
 }
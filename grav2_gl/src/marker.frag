@@ -0,0 +1,16 @@
+/*
+	Ring-shaped marker for an attractor (marker.vert):
+	blue if it attracts, red if it repels.
+*/
+#version 450 core
+
+flat in float mass;
+out vec4 output_color;
+
+void main() {
+	float r = length(2.0 * gl_PointCoord - 1.0);
+	if (r > 1.0 || r < 0.6) {
+		discard;
+	}
+	output_color = mass >= 0.0 ? vec4(0.3, 0.7, 1.0, 1.0) : vec4(1.0, 0.3, 0.2, 1.0);
+}
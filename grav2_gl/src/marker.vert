@@ -0,0 +1,23 @@
+/*
+	Draw each attractor (attractor.glsl) as a point,
+	at the same place as its mass would be drawn in the density map (density.glsl).
+	Use with glDrawArrays(POINTS, 0, num_attractors), without vertex attributes.
+*/
+#version 450 core
+
+#include "attractor.glsl"
+
+uniform float scale = 200.0;                    // pixels per unit length
+uniform vec2 offset = vec2(0.0, 0.0);           // position at the center of the viewport
+uniform vec2 viewport = vec2(1024.0, 1024.0);   // in pixels
+uniform float point_size = 12.0;                // in pixels, for unit mass
+
+flat out float mass;
+
+void main() {
+	Attractor a = attractors[gl_VertexID];
+	vec2 pix = (a.pos - offset) * scale; // relative to the center, y pointing down
+	gl_Position = vec4(2.0 * pix.x / viewport.x, -2.0 * pix.y / viewport.y, 0.0, 1.0);
+	gl_PointSize = point_size * clamp(sqrt(abs(a.mass)), 0.5, 4.0);
+	mass = a.mass;
+}
@@ -1,6 +1,11 @@
 /*
 	Verlet (leapfrog) integration:
 	update velocities and positions given accelartions.
+	Reads the current pos, vel and writes the next ones to separate images (see PingPong).
+
+	Velocities live half a step behind positions, which makes the scheme time-reversible:
+	after one extra velocity update (drift = 0), stepping with -dt retraces the trajectory
+	(see State::reverse).
 
 	https://en.wikipedia.org/wiki/Leapfrog_integration
 */
@@ -8,22 +13,28 @@
 
 layout (local_size_x = 16, local_size_y = 16) in;
 
-layout (binding = 0, rg32f) uniform          image2D pos;
-layout (binding = 1, rg32f) uniform          image2D vel;
-layout (binding = 2, rg32f) uniform readonly image2D acc;
+layout (binding = 0, rg32f) uniform readonly  image2D pos;
+layout (binding = 1, rg32f) uniform readonly  image2D vel;
+layout (binding = 2, rg32f) uniform readonly  image2D acc;
+layout (binding = 3, rg32f) uniform writeonly image2D pos_next;
+layout (binding = 4, rg32f) uniform writeonly image2D vel_next;
 
 uniform float dt = 0.0001;
+uniform float drift = 1.0; // 0: only update velocities
 
 void main(){
 	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
+	if (any(greaterThanEqual(xy, imageSize(pos)))) {
+		return; // the dispatch is rounded up to whole work groups
+	}
 
 	vec2 p = imageLoad(pos, xy).xy;
 	vec2 v = imageLoad(vel, xy).xy;
 	vec2 a = imageLoad(acc, xy).xy;
 
 	v = v + a * dt;
-	p = p + v * dt;
+	p = p + v * (dt * drift);
 
-	imageStore(pos, xy, vec4(p, 0.0, 0.0));
-	imageStore(vel, xy, vec4(v, 0.0, 0.0));
+	imageStore(pos_next, xy, vec4(p, 0.0, 0.0));
+	imageStore(vel_next, xy, vec4(v, 0.0, 0.0));
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+extern crate gl_img;
 extern crate gl_safe;
 extern crate gl_win;
 extern crate image;
@@ -8,15 +9,9 @@ use gl::*;
 use gl_obj::*;
 use gl_safe::*;
 use gl_win::*;
-use glutin::event::ElementState;
-use glutin::event::MouseButton;
-use glutin::event::MouseScrollDelta;
-use glutin::event::VirtualKeyCode;
 use rand::prelude::*;
 use std::cell::Cell;
 use std::f32::consts::PI;
-use std::sync::Arc;
-use std::time;
 use structopt::StructOpt;
 
 /// OpenGL water simulation.
@@ -34,75 +29,153 @@ struct Args {
 	#[structopt(long, default_value = "0.001")]
 	dt: f32,
 
-	/// Time steps per rendered frame.
+	/// Simulated time per wall-clock second.
+	#[structopt(long, default_value = "0.6")]
+	rate: f64,
+
+	/// Maximum time steps per frame, when the GPU cannot keep up with --rate.
+	#[structopt(long, default_value = "100")]
+	max_steps: u32,
+
+	/// Time steps per frame with --headless.
 	#[structopt(short, long, default_value = "10")]
 	steps_per_frame: u32,
 
 	/// Render scaling
 	#[structopt(long, default_value = "200.0")]
 	scale: f32,
+
+	/// Time constant (seconds) of the exponential decay of the density map:
+	/// longer shows longer trails, with less noise.
+	#[structopt(long, default_value = "0.025")]
+	decay_time: f32,
+
+	/// Start in fullscreen mode (toggle with F11).
+	#[structopt(long)]
+	fullscreen: bool,
+
+	/// Run without a window (e.g. on a machine without display):
+	/// simulate --frames frames, save the last one to --output and exit.
+	#[structopt(long)]
+	headless: bool,
+
+	/// Number of frames to simulate with --headless.
+	#[structopt(long, default_value = "100")]
+	frames: u32,
+
+	/// Image file for the last frame with --headless.
+	#[structopt(long, default_value = "gravity.png")]
+	output: String,
 }
 
 fn main() {
 	let args = Args::from_args();
 
+	if args.headless {
+		let context = init_gl_headless(args.width, args.height).unwrap_or_else(|e| panic!("{}", e));
+		println!("headless GL context ({})", context.backend());
+		run_headless(&args);
+		return;
+	}
+
 	// window
 	let size = uvec2(args.width, args.height);
 	let (w, h) = (size.0, size.1);
-	let (win, ev) = init_gl_window(w, h, "gravity");
+	let (win, mut ev) = init_gl_window(w, h, "gravity");
+
+	if args.fullscreen {
+		set_fullscreen(&win, true);
+	}
+
+	let (w, h) = physical_size(&win);
+	let mut s = State::new(&args, uvec2(w, h), scale_factor(&win) as f32);
+
+	run_app(&win, &mut ev, &mut s);
 
-	let s = State::new(&args);
+	// Delete all GL objects while the context is still current, then check for leaks.
+	drop(s);
+	report_live_objects();
+}
+
+// Simulate without a window, and save the last frame.
+// The headless context must be current.
+fn run_headless(args: &Args) {
+	let mut s = State::new(args, uvec2(args.width, args.height), 1.0);
+	for _ in 0..args.frames {
+		s.advance(args.steps_per_frame, HEADLESS_FRAME_TIME);
+	}
+	s.save_frame(&args.output);
+	drop(s);
+	report_live_objects();
+}
 
-	// continuously pump redraws
-	let proxy = ev.create_proxy();
-	std::thread::spawn(move || loop {
-		proxy.send_event(()).expect("send event");
-		std::thread::sleep(time::Duration::from_millis(6));
-	});
+// Wall time per frame assumed with --headless, for the decay of the density map.
+const HEADLESS_FRAME_TIME: f32 = 1.0 / 60.0;
 
-	// infinite event loop
-	run_event_loop(ev, win, s);
+/// Vertex of the full-screen quad, see texture.vert.
+#[derive(Clone, Copy, Vertex)]
+struct QuadVertex {
+	vertex_pos: vec2,
+	vertex_tex_coord: vec2,
+}
+
+impl QuadVertex {
+	fn new(vertex_pos: vec2, vertex_tex_coord: vec2) -> Self {
+		Self { vertex_pos, vertex_tex_coord }
+	}
 }
 
 struct State {
 	scale: f32,
+	scale_factor: f32,
 	p_render: Program,
 	p_density: Program,
 	p_decay: Program,
-	pos: Texture,
-	density: Texture,
+	u_scale: Uniform<f32>,
+	u_weight: Uniform<f32>,
+	u_decay: Uniform<f32>,
+	decay_time: f32,
+	pos: Texture<Rg32f>,
+	density: Texture<R32ui>,
 	vao: VertexArray,
-	time_steps_per_draw: u32,
-	mouse_down: bool,
+	_vertex_buffer: Buffer<QuadVertex>,
+	scheduler: Scheduler,
 	frames: Cell<i32>,
 }
 
 impl State {
-	fn new(args: &Args) -> Self {
+	// `view_size`: size of the window in physical pixels,
+	// `scale_factor`: ratio of physical to logical pixels (HiDPI).
+	fn new(args: &Args, view_size: uvec2, scale_factor: f32) -> Self {
 		let (pos, vel) = Self::initial_particles(&args);
 		let size = uvec2(args.width, args.height);
-		let p_render = Program::new(&[
-			//
-			Shader::new_vert(include_str!("texture.vert")),
-			Shader::new_frag(include_str!("draw.frag")),
-		]);
+		let shaders = Self::shaders();
+		let p_render = Self::prog(&shaders, &[(VERTEX_SHADER, "texture.vert"), (FRAGMENT_SHADER, "draw.frag")]);
+		let (vao, vertex_buffer) = Self::vao(&p_render).unwrap_or_else(|e| panic!("{}", e));
+		let p_density = Self::prog(&shaders, &[(COMPUTE_SHADER, "density.glsl")]);
+		let p_decay = Self::prog(&shaders, &[(COMPUTE_SHADER, "decay.glsl")]);
 
 		Self {
 			scale: args.scale,
-			p_decay: Self::compute_prog(include_str!("decay.glsl")),
-			p_density: Self::compute_prog(include_str!("density.glsl")),
+			scale_factor,
+			u_scale: p_density.uniform("scale"),
+			u_weight: p_density.uniform("weight"),
+			u_decay: p_decay.uniform("decay"),
+			decay_time: args.decay_time,
+			p_decay,
+			p_density,
 			p_render,
 			pos: Self::vec_to_tex(size, &pos),
-			density: Texture::new2d(RGBA8UI, size).filter_nearest(),
-			vao: Self::vao(p_render),
-			time_steps_per_draw: args.steps_per_frame,
+			density: Texture::new2d(view_size).filter_nearest(),
+			vao,
+			_vertex_buffer: vertex_buffer,
+			scheduler: Scheduler::new(args.dt as f64, args.rate).max_steps_per_frame(args.max_steps),
 			frames: Cell::new(0),
-			mouse_down: false,
 		}
 	}
 
-	fn vec_to_tex(size: uvec2, data: &[vec2]) -> Texture {
-		Texture::new2d(RG32F, size).sub_image2d(0, 0, 0, size.0, size.1, RG, FLOAT, data)
+	fn vec_to_tex(size: uvec2, data: &[vec2]) -> Texture<Rg32f> {
+		Texture::from_data2d(size, data)
 	}
 
 	fn initial_particles(args: &Args) -> (Vec<vec2>, Vec<vec2>) {
@@ -130,20 +203,26 @@ impl State {
 
 	fn steps(&mut self, n: u32) {
 		// TODO
-		self.update_density();
 	}
 
-	fn update_density(&self) {
+	// Decay the density map by the time constant over `wall_dt` seconds, and add the current particles.
+	fn update_density(&self, wall_dt: f32) {
+		let decay = (-wall_dt / self.decay_time).exp();
+
+		self.u_decay.set(decay);
 		self.density.bind_image_unit(0, READ_WRITE);
-		self.exec(self.p_decay);
+		let size = self.density.size();
+		self.exec_over(&self.p_decay, uvec2(size.0, size.1));
 
-		self.p_density.set1f("scale", self.scale);
-		self.pos.bind_image_unit(0, READ_WRITE); // TODO
+		self.u_scale.set(self.scale * self.scale_factor);
+		self.u_weight.set(1.0 - decay);
+		self.pos.bind_image_unit(0, READ_ONLY);
 		self.density.bind_image_unit(1, READ_WRITE);
-		self.exec(self.p_density);
+		self.exec(&self.p_density);
 	}
 
-	fn draw(&self, _w: &Window) {
+	// Draw the density map into the current framebuffer.
+	fn render(&self) {
 		glClearColor(0.5, 0.5, 0.5, 1.0);
 		glClear(gl::COLOR_BUFFER_BIT);
 
@@ -154,137 +233,141 @@ impl State {
 		glDrawArrays(gl::TRIANGLE_STRIP, 0, 4);
 	}
 
-	fn exec(&self, p: Program) {
+	// Run a compute program over all particles.
+	fn exec(&self, p: &Program) {
 		let xy = self.pos.size();
-		p.compute_and_sync(uvec3(xy.0, xy.1, 1))
-	}
-
-	fn on_cursor_moved(&self, position: (f64, f64)) {
-		if self.mouse_down {
-			let (w, h) = (self.pos.size().0 as i32, self.pos.size().1 as i32);
-			let (x, y) = ((position.0) as i32, (position.1) as i32);
-			if x >= 0 && x < (w as i32) && y >= 0 && y < (h as i32) {
-				let x = (x - w / 2) as f32;
-				let y = (y - h / 2) as f32;
-				let x = x / self.scale;
-				let y = y / self.scale;
-				//self.p_accel.set2f("sun_pos", x, y);
-			}
-		}
+		self.exec_over(p, uvec2(xy.0, xy.1))
 	}
 
-	fn on_mouse_input(&mut self, _button: MouseButton, state: ElementState) {
-		self.mouse_down = match state {
-			ElementState::Pressed => true,
-			ElementState::Released => false,
-		};
+	// Run a compute program with 16x16 work groups over `size` invocations, rounded up
+	// (the shaders skip the invocations outside their image).
+	fn exec_over(&self, p: &Program, size: uvec2) {
+		let (w, h) = (size.0.div_ceil(16) * 16, size.1.div_ceil(16) * 16);
+		p.compute_and_sync(uvec3(w, h, 1)).unwrap_or_else(|e| panic!("{}", e))
 	}
 
-	fn on_redraw_requested(&mut self, win: &Window) {
-		self.draw(&win);
-		win.swap_buffers().unwrap();
-		self.steps(self.time_steps_per_draw);
+	// Simulate the time steps of one frame, `wall_dt` seconds after the previous one.
+	fn advance(&mut self, steps: u32, wall_dt: f32) {
+		self.steps(steps);
+		self.update_density(wall_dt);
 		self.frames.set(self.frames.get() + 1);
 		//let secs = self.start.elapsed().as_secs_f32();
 		//let fps = self.frames.get() as f32 / secs;
 		//dbg!(fps);
 	}
 
+	// Render the current state into an offscreen image of the window size and save it (for --headless).
+	fn save_frame(&self, fname: &str) {
+		let size = self.density.size();
+		let color = Texture::<Rgba8>::new2d(uvec2(size.0, size.1));
+		let fb = Framebuffer::new(&color).unwrap_or_else(|e| panic!("{}", e));
+		fb.bind();
+		self.render();
+		gl_img::save_framebuffer(fname, &fb, gl::COLOR_ATTACHMENT0);
+		println!("saved {}", fname);
+	}
+
+	// Show the simulated time and measured performance in the window title.
+	fn show_stats(&self, win: &Window) {
+		let s = &self.scheduler;
+		let state = if s.is_paused() { "paused" } else { "running" };
+		let lagging = if s.is_lagging() { " (lagging)" } else { "" };
+		let title = format!("gravity: t = {:.3} ({}), {:.0} fps, {:.0} steps/s{}", s.time(), state, s.fps(), s.steps_per_second(), lagging);
+		win.window().set_title(&title);
+	}
+
 	fn zoom(&mut self, scale: f32) {
 		self.scale = self.scale * scale;
 		self.density.bind_image_unit(0, READ_WRITE);
 	}
 
-	fn on_user_event(&self, win: &Window) {
-		win.window().request_redraw()
+	// Shader sources embedded in the binary.
+	fn shaders() -> Preprocessor {
+		Preprocessor::new(
+			EmbeddedFs::new()
+				.file("decay.glsl", include_str!("../decay.glsl"))
+				.file("density.glsl", include_str!("../density.glsl"))
+				.file("draw.frag", include_str!("../draw.frag"))
+				.file("fixed_point.glsl", include_str!("../fixed_point.glsl"))
+				.file("texture.vert", include_str!("../texture.vert")),
+		)
 	}
 
-	fn on_cursor_entered(&self) {}
+	// Compile and link shader files, e.g. `&[(COMPUTE_SHADER, "decay.glsl")]`.
+	fn prog(shaders: &Preprocessor, stages: &[(GLenum, &str)]) -> Program {
+		let build = || {
+			let stages = stages.iter().map(|(typ, path)| shaders.shader(*typ, path)).collect::<Result<Vec<_>, _>>()?;
+			Program::new(&stages)
+		};
+		build().unwrap_or_else(|e| panic!("{}", e))
+	}
 
-	fn on_cursor_left(&self) {}
+	// The vertex array, and the buffer it refers to (which must be kept alive).
+	fn vao(prog: &Program) -> Result<(VertexArray, Buffer<QuadVertex>), String> {
+		let vertices = [
+			QuadVertex::new(vec2(-1.0, 1.0), vec2(0.0, 0.0)),
+			QuadVertex::new(vec2(-1.0, -1.0), vec2(0.0, 1.0)),
+			QuadVertex::new(vec2(1.0, 1.0), vec2(1.0, 0.0)),
+			QuadVertex::new(vec2(1.0, -1.0), vec2(1.0, 1.0)),
+		];
+		let buffer = Buffer::new(&vertices, 0);
+		let vao = VertexArray::from_layout(prog, &buffer)?;
+		Ok((vao, buffer))
+	}
+}
 
-	fn on_mouse_wheel(&mut self, delta: MouseScrollDelta) {
-		let mut zoom = |dy| {
-			if dy > 0.0 {
-				self.zoom(1.05)
-			}
-			if dy < 0.0 {
-				self.zoom(0.95)
-			}
-		};
-		match delta {
-			MouseScrollDelta::LineDelta(_x, y) => zoom(y),
-			MouseScrollDelta::PixelDelta(phys) => zoom(phys.y as f32),
+impl App for State {
+	fn update(&mut self, win: &Window, dt: f32) {
+		let steps = self.scheduler.steps(dt);
+		self.advance(steps, dt);
+		if self.frames.get() % 30 == 0 {
+			self.show_stats(win);
 		}
 	}
 
-	fn on_key(&mut self, k: VirtualKeyCode) {
+	fn draw(&mut self, _win: &Window) {
+		self.render();
+	}
+
+	fn on_key(&mut self, win: &Window, k: VirtualKeyCode, state: ElementState) {
+		if state != ElementState::Pressed {
+			return;
+		}
 		println!("key {:?}", k);
-		//use VirtualKeyCode::*;
 		match k {
 			VirtualKeyCode::Equals | VirtualKeyCode::Plus => self.zoom(2.0),
 			VirtualKeyCode::Minus | VirtualKeyCode::Underline => self.zoom(0.5),
+			VirtualKeyCode::Space => self.scheduler.set_paused(!self.scheduler.is_paused()),
+			VirtualKeyCode::N | VirtualKeyCode::Period => {
+				self.scheduler.set_paused(true);
+				self.scheduler.single_step();
+			}
+			VirtualKeyCode::Up => self.scheduler.set_rate(self.scheduler.rate() * 2.0),
+			VirtualKeyCode::Down => self.scheduler.set_rate(self.scheduler.rate() / 2.0),
+			VirtualKeyCode::F11 => set_fullscreen(win, !is_fullscreen(win)),
 			_ => (),
 		}
+		self.show_stats(win);
 	}
 
-	fn compute_prog(src: &str) -> Program {
-		Program::new(&[Shader::new_comp(src)])
-	}
-
-	fn vao(prog: Program) -> VertexArray {
-		let v_pos = [
-			//
-			vec2(-1.0, 1.0),
-			vec2(-1.0, -1.0),
-			vec2(1.0, 1.0),
-			vec2(1.0, -1.0),
-		];
-		let v_pos_buf = Buffer::new(&v_pos, 0);
-
-		let v_texc = [
-			//
-			vec2(0.0, 0.0),
-			vec2(0.0, 1.0),
-			vec2(1.0, 0.0),
-			vec2(1.0, 1.0),
-		];
-		let v_texc_buf = Buffer::new(&v_texc, 0);
-
-		let v_pos_attr = prog.attrib_location("vertex_pos").unwrap();
-		let v_texc_attr = prog.attrib_location("vertex_tex_coord").unwrap();
-		VertexArray::create()
-			.enable_attrib(v_pos_attr)
-			.attrib_format(v_pos_attr, 2, gl::FLOAT, false, 0)
-			.vertex_buffer(v_pos_attr, v_pos_buf, 0, sizeof(v_pos[0]))
-			.enable_attrib(v_texc_attr)
-			.attrib_format(v_texc_attr, 2, gl::FLOAT, false, 0)
-			.vertex_buffer(v_texc_attr, v_texc_buf, 0, sizeof(v_texc[0]))
+	fn on_scroll(&mut self, _win: &Window, delta: f32) {
+		if delta > 0.0 {
+			self.zoom(1.05)
+		}
+		if delta < 0.0 {
+			self.zoom(0.95)
+		}
 	}
-}
 
-fn run_event_loop(ev: EventLoop, win: Arc<Window>, mut s: State) {
-	ev.run(move |event, _, control_flow| {
-		*control_flow = ControlFlow::Wait;
-		match event {
-			Event::RedrawRequested(_) => s.on_redraw_requested(&win),
-			Event::UserEvent(_) => s.on_user_event(&win),
-			Event::LoopDestroyed => return,
-			Event::WindowEvent { event, .. } => match event {
-				WindowEvent::CursorMoved { position, .. } => s.on_cursor_moved((position.x, position.y)),
-				WindowEvent::MouseInput { state, button, .. } => s.on_mouse_input(button, state),
-				WindowEvent::MouseWheel { delta, .. } => s.on_mouse_wheel(delta),
-				WindowEvent::CursorEntered { .. } => s.on_cursor_entered(),
-				WindowEvent::CursorLeft { .. } => s.on_cursor_left(),
-				WindowEvent::KeyboardInput { input, .. } => {
-					if let Some(k) = input.virtual_keycode {
-						s.on_key(k)
-					}
-				}
-				WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-				_ => (),
-			},
-			_ => (),
+	// Reallocate the density map to the new window size (in physical pixels),
+	// so that it is drawn 1:1 and keeps its aspect ratio.
+	// Keep showing the same region when moved to a display with a different DPI.
+	fn on_resize(&mut self, _win: &Window, size: (u32, u32), scale_factor: f64) {
+		self.scale_factor = scale_factor as f32;
+		let current = self.density.size();
+		if size.0 == 0 || size.1 == 0 || (size.0 == current.0 && size.1 == current.1) {
+			return; // minimized, or unchanged
 		}
-	});
+		self.density = Texture::new2d(uvec2(size.0, size.1)).filter_nearest();
+	}
 }
@@ -1,33 +1,30 @@
 /*
+	Add the particles to the density map (see fixed_point.glsl).
 
+	Each particle adds `weight` = 1 - decay (see decay.glsl), so that the map converges
+	to the number of particles per pixel, averaged over the decay time.
 */
 #version 450 core
 
 layout (local_size_x = 16, local_size_y = 16) in;
 
-layout(binding = 0, rg32f) uniform image2D  pos; 
-layout(binding = 1, rgba8ui) uniform uimage2D photons; // output added here
+layout(binding = 0, rg32f) uniform readonly image2D pos;
+layout(binding = 1, r32ui) uniform uimage2D density; // output added here
 
 uniform float scale = 200.0;
+uniform float weight = 0.5; // particles added per particle
 
-// Colors represented as int,
-// because atomicAdd only takes ints.
-#define RGB(r, g, b) (((r)<<0) | ((g)<<8) | ((b)<<16))
-#define RED    (RGB(2, 0, 0))
-#define YELLOW (RGB(1, 1, 0))
-#define GREEN  (RGB(0, 2, 0))
-#define CYAN   (RGB(0, 1, 1))
-#define BLUE   (RGB(0, 0, 2))
-#define PURPLE (RGB(1, 0, 1))
-
-#define WEIGHT (8)
+#include "fixed_point.glsl"
 
 void main() {
 	ivec2 xy = ivec2(gl_GlobalInvocationID.xy);
-	ivec2 size = imageSize(photons);
+	if (any(greaterThanEqual(xy, imageSize(pos)))) {
+		return; // the dispatch is rounded up to whole work groups
+	}
+	ivec2 size = imageSize(density);
 
 	vec2 p = imageLoad(pos, xy).xy;
 	ivec2 pix = ivec2(p * scale + size / 2);
-	imageAtomicAdd(photons, pix, WEIGHT);
-
+	uint w = max(1u, uint(weight * ONE + 0.5));
+	imageAtomicAdd(density, pix, w);
 }
@@ -1,19 +1,20 @@
 /*
-
+	Map the particle density (particles per pixel, see fixed_point.glsl) to a brightness,
+	logarithmically so that both sparse and dense regions are visible.
 */
 #version 450 core
 
 in  vec2 frag_tex_coord;
 out vec4 output_color;
 
-layout(binding = 3) uniform usampler2D photon; // photon map (see photon.glsl)
+#include "fixed_point.glsl"
+
+layout(binding = 3) uniform usampler2D density; // density map (see density.glsl)
+
+uniform float exposure = 0.5;
 
 void main() {
-	vec2 start = frag_tex_coord;
-	uvec3 rgb = texture(photon, start).rgb;
-	uint num_particles = rgb.r | rgb.g << 8 | rgb.b << 16;
-	float density = float(num_particles);
-	float gamma = sqrt(density) * 0.35;
+	float d = float(texture(density, frag_tex_coord).r) / ONE;
 	vec3 color = vec3(1.0, 0.5, 0.2);
-	output_color = vec4(gamma * color, 1.0);
-}
\ No newline at end of file
+	output_color = vec4(exposure * log(1.0 + d) * color, 1.0);
+}
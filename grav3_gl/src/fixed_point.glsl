@@ -0,0 +1,10 @@
+/*
+	The density map holds the (time-averaged) number of particles per pixel
+	in fixed point, with ONE units per particle, so that it can be accumulated
+	with imageAtomicAdd into an r32ui image (density.glsl):
+	atomic addition on floats is not available in core OpenGL.
+
+	With 1024x1024 particles, the total never exceeds 2^28.
+*/
+
+#define ONE 256.0